@@ -0,0 +1,214 @@
+use random_carnegie::code::SetupCode;
+use random_carnegie::departments::Building;
+use random_carnegie::options::{
+    Expansion, LimitTypes, Options, PerRow, Permanent, Players, Sampling, Tiles,
+};
+use random_carnegie::seed::Seed;
+use random_carnegie::version::Version;
+use random_carnegie::Setup;
+use serde::Serialize;
+use std::process::ExitCode;
+use std::str::FromStr;
+
+const USAGE: &str = "\
+Usage: random-carnegie-cli [OPTIONS] [SEED]
+
+Prints the setup for SEED (a random one if omitted), which is either a number or
+DEPARTMENTS-CARDS with separate seeds for the departments and the blocked spaces.
+
+Options:
+  --players all|4|3|2              Show for (default: 4)
+  --tiles base|both|expansion      Department tiles (default: base)
+  --limit 4|5|6|8                  Different departments per row (default: 4)
+  --permanent 0|0p|1|1p|2          Permanent departments per row (default: 1)
+                                   Both also accept four comma-separated values, one per row
+  --expansion any|1p|1|2|3|4       Expansion departments per row with both (default: any)
+  --sampling draw|uniform          Draw tiles or choose uniformly (default: draw)
+  --exclude N,N,...                Departments which are never used
+  --require N,N,...                Departments which are always used
+  --forbid-pairs N-N,...           Never both departments of a pair
+  --require-pairs N-N,...          The second department whenever the first is used
+  --algorithm 1|2                  Version of the generator (default: latest)
+  --json                           Print the setup as JSON
+  --help                           Print this help";
+
+#[derive(Serialize)]
+struct Output<'a> {
+    seed: Seed,
+    version: Version,
+    options: OutputOptions,
+    setup: &'a Setup,
+}
+
+/// The options with lists of tile numbers instead of the bit sets of the local storage.
+#[derive(Serialize)]
+struct OutputOptions {
+    players: Players,
+    tiles: Tiles,
+    limit: PerRow<LimitTypes>,
+    permanent: PerRow<Permanent>,
+    sampling: Sampling,
+    excluded: Vec<Building>,
+    required: Vec<Building>,
+    rules: OutputRules,
+    expansion: Expansion,
+}
+
+#[derive(Serialize)]
+struct OutputRules {
+    forbidden: Vec<(Building, Building)>,
+    required: Vec<(Building, Building)>,
+}
+
+impl From<&Options> for OutputOptions {
+    fn from(options: &Options) -> Self {
+        Self {
+            players: options.players,
+            tiles: options.tiles,
+            limit: options.limit,
+            permanent: options.permanent,
+            sampling: options.sampling,
+            excluded: options.excluded.iter().collect(),
+            required: options.required.iter().collect(),
+            rules: OutputRules {
+                forbidden: options.rules.forbidden.iter().collect(),
+                required: options.rules.required.iter().collect(),
+            },
+            expansion: options.expansion,
+        }
+    }
+}
+
+fn parse<T: FromStr>(name: &str, value: Option<String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("missing value for {name}"))?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid value for {name}: {value}"))
+}
+
+fn print_text(seed: Seed, options: &Options, setup: &Setup) {
+    let code = SetupCode {
+        players: options.players,
+        setup: setup.clone(),
+    };
+    println!("Seed: {seed}");
+    println!("Setup code: {code}");
+    println!("Fingerprint: {}", code.fingerprint());
+    println!();
+    if let Ok(layouts) = random_carnegie::count_layouts(options) {
+        println!("Possible department grids: {layouts}");
+    }
+    println!();
+    println!("Pull list: {}", setup.pull_list());
+    println!();
+    println!("Departments (* = permanent):");
+    for row in &setup.departments {
+        let row = row
+            .iter()
+            .map(|d| {
+                if d.copies > 0 {
+                    let blue = if d.building.blue() { '*' } else { ' ' };
+                    format!("{:>2}{}×{}", d.building.value(), blue, d.copies)
+                } else {
+                    " ".repeat(5)
+                }
+            })
+            .collect::<Vec<_>>();
+        println!("  {}", row.join("  ").trim_end());
+    }
+
+    if options.players.disks() > 0 {
+        println!();
+        println!("Blocked donations:");
+        for row in &setup.donations {
+            let row = row
+                .iter()
+                .map(|e| if *e { "●" } else { "○" })
+                .collect::<Vec<_>>();
+            println!("  {}", row.join(" "));
+        }
+        println!();
+        println!("Blocked cities:");
+        for (city, count) in &setup.cities {
+            println!("  {count} × {} ({})", city.name(), city.region());
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let mut seed = None;
+    let mut version = Version::LATEST;
+    let mut options = Options::default();
+    let mut json = false;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let result = match arg.as_str() {
+            "--players" => parse(&arg, args.next()).map(|v| options.players = v),
+            "--tiles" => parse(&arg, args.next()).map(|v| options.tiles = v),
+            "--limit" => parse(&arg, args.next()).map(|v| options.limit = v),
+            "--permanent" => parse(&arg, args.next()).map(|v| options.permanent = v),
+            "--sampling" => parse(&arg, args.next()).map(|v| options.sampling = v),
+            "--expansion" => parse(&arg, args.next()).map(|v| options.expansion = v),
+            "--exclude" => parse(&arg, args.next()).map(|v| options.excluded = v),
+            "--require" => parse(&arg, args.next()).map(|v| options.required = v),
+            "--forbid-pairs" => parse(&arg, args.next()).map(|v| options.rules.forbidden = v),
+            "--require-pairs" => parse(&arg, args.next()).map(|v| options.rules.required = v),
+            "--algorithm" => parse(&arg, args.next()).map(|v| version = v),
+            "--json" => {
+                json = true;
+                Ok(())
+            }
+            "--help" | "-h" => {
+                println!("{USAGE}");
+                return ExitCode::SUCCESS;
+            }
+            _ if seed.is_none() && !arg.starts_with('-') => {
+                parse("SEED", Some(arg)).map(|v| seed = Some(v))
+            }
+            _ => Err(format!("unexpected argument: {arg}")),
+        };
+        if let Err(err) = result {
+            eprintln!("{err}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    }
+
+    let mut clamped = options;
+    clamped.clamp();
+    if version != Version::V1 && clamped != options {
+        eprintln!(
+            "with --tiles {} only --limit {} and --permanent {} are possible",
+            options.tiles,
+            options.tiles.limits()[0],
+            options.tiles.permanents()[0]
+        );
+        return ExitCode::from(2);
+    }
+
+    let seed = seed.unwrap_or_else(Seed::random);
+    let setup = match random_carnegie::generate(seed, version, &options) {
+        Ok(setup) => setup,
+        Err(err) => {
+            eprintln!("{err}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if json {
+        let output = Output {
+            seed,
+            version,
+            options: OutputOptions::from(&options),
+            setup: &setup,
+        };
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&output).expect("setup is serializable")
+        );
+    } else {
+        print_text(seed, &options, &setup);
+    }
+
+    ExitCode::SUCCESS
+}
@@ -1,243 +1,463 @@
-use crate::buildings::building::Building;
-use crate::{Data, Players};
-use rand::seq::SliceRandom;
-use rand::SeedableRng;
-use rand_pcg::Pcg64Mcg;
+use crate::Data;
+use random_carnegie::catalog::{Edition, Info};
+use random_carnegie::departments::{Building, BuildingSet};
+use random_carnegie::options::{
+    Expansion, LimitTypes, Options, PerRow, Permanent, Rules, Sampling, Tiles,
+};
+use random_carnegie::validate::{self, Violation};
 use serde::{Deserialize, Serialize};
-use serde_repr::{Deserialize_repr, Serialize_repr};
-use std::collections::BTreeMap;
-use std::fmt::{Display, Formatter};
-use yew::{function_component, html, Html, Properties};
+use web_sys::HtmlSelectElement;
+use yew::{
+    function_component, html, use_node_ref, use_state, Callback, Event, Html, MouseEvent, NodeRef,
+    Properties, TargetCast,
+};
+use yew_bootstrap::component::{Button, ButtonSize};
 use yew_bootstrap::icons::BI;
-use yewdux::functional::use_store;
+use yew_bootstrap::util::Color;
+use yewdux::dispatch::Dispatch;
 use yewdux::mrc::Mrc;
 use yewdux::store::Store;
 
-mod building {
-    use crate::buildings::Tiles;
-
-    #[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
-    pub(super) struct Building(u8);
-
-    impl Building {
-        #[inline]
-        pub(super) fn new(value: u8) -> Self {
-            Self(value)
-        }
-
-        #[inline]
-        pub(super) fn value(&self) -> u8 {
-            self.0
-        }
-
-        #[inline]
-        pub(super) fn row(&self) -> usize {
-            (((self.0 - 1) >> 2) & 3) as usize
-        }
-
-        #[inline]
-        pub(super) fn blue(&self) -> bool {
-            (if self.0 <= 16 { self.0 - 1 } else { self.0 }) & 3 == 3
-        }
-
-        #[inline]
-        pub(super) fn is_tile(&self, tiles: Tiles) -> bool {
-            match tiles {
-                Tiles::Base => self.0 <= 16,
-                Tiles::Both => true,
-                Tiles::Expansion => self.0 >= 17,
-            }
-        }
-    }
-}
-
-pub(crate) struct Buildings(Vec<Building>);
-
-impl Default for Buildings {
-    fn default() -> Self {
-        let mut b = Vec::with_capacity(64);
-        for i in 1..=32 {
-            b.push(Building::new(i));
-            b.push(Building::new(i));
-        }
-        Self(b)
-    }
-}
-
-impl Buildings {
-    pub(crate) fn rand(&mut self, seed: u64) {
-        self.0.sort();
-        let mut rng = Pcg64Mcg::seed_from_u64(seed);
-        self.0.shuffle(&mut rng);
-    }
-}
-
-impl Display for Buildings {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let mut r = Vec::from_iter(self.0.iter().take(32));
-        r.sort();
-        for (i, b) in r.iter().enumerate() {
-            if i != 0 {
-                write!(f, ",")?;
-            }
-            write!(f, "{:02}", b.value())?;
-        }
-        Ok(())
-    }
-}
-
 #[derive(Properties, PartialEq)]
 pub(crate) struct Props {
     pub data: Mrc<Data>,
+    pub on_change: Callback<Options>,
+    /// Toggles whether a row is kept when re-rolling.
+    pub on_lock: Callback<usize>,
+    /// Replaces a department of a row (the first) with another one (the second).
+    pub on_replace: Callback<(usize, Building, Building)>,
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Default, Deserialize, Serialize)]
-enum Tiles {
-    #[default]
-    Base,
-    Both,
-    Expansion,
-}
-
-#[derive(Copy, Clone, Eq, PartialEq, Default, Deserialize_repr, Serialize_repr)]
-#[repr(u8)]
-enum LimitTypes {
-    #[default]
-    Four = 4,
-    Five = 5,
-    Six = 6,
-    All = 8,
+/// The saved department settings.
+///
+/// Settings saved before `limit` and `permanent` were per row contain a single value, which
+/// [`PerRow`] reads as the value for all rows.
+#[derive(Store, PartialEq, Default, Deserialize, Serialize, Clone)]
+#[store(storage = "local")]
+#[serde(default)]
+pub(crate) struct State {
+    tiles: Tiles,
+    limit: PerRow<LimitTypes>,
+    permanent: PerRow<Permanent>,
+    sampling: Sampling,
+    expansion: Expansion,
+    excluded: BuildingSet,
+    required: BuildingSet,
+    rules: Rules,
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Default, Deserialize, Serialize)]
-enum Permanent {
-    Zero,
-    ZeroPlus,
-    #[default]
-    One,
-    OnePlus,
-    Two,
-}
+impl State {
+    pub(crate) fn load(options: &mut Options) {
+        let state = Dispatch::<Self>::new().get();
+        options.tiles = state.tiles;
+        options.limit = state.limit;
+        options.permanent = state.permanent;
+        options.sampling = state.sampling;
+        options.expansion = state.expansion;
+        options.excluded = state.excluded;
+        options.required = state.required;
+        options.rules = state.rules;
+    }
 
-impl Permanent {
-    fn min(&self) -> usize {
-        match self {
-            Permanent::Zero => 0,
-            Permanent::ZeroPlus => 0,
-            Permanent::One => 1,
-            Permanent::OnePlus => 1,
-            Permanent::Two => 2,
-        }
+    pub(crate) fn save(options: &Options) {
+        Dispatch::<Self>::new().set(Self {
+            tiles: options.tiles,
+            limit: options.limit,
+            permanent: options.permanent,
+            sampling: options.sampling,
+            expansion: options.expansion,
+            excluded: options.excluded,
+            required: options.required,
+            rules: options.rules,
+        });
     }
+}
 
-    fn max(&self) -> usize {
-        match self {
-            Permanent::Zero => 0,
-            Permanent::ZeroPlus => 2,
-            Permanent::One => 1,
-            Permanent::OnePlus => 2,
-            Permanent::Two => 2,
+const LIMITS: [(LimitTypes, &str); 4] = [
+    (LimitTypes::Four, "4"),
+    (LimitTypes::Five, "up to 5"),
+    (LimitTypes::Six, "up to 6"),
+    (LimitTypes::All, "up to 8"),
+];
+
+const PERMANENT: [(Permanent, &str); 5] = [
+    (Permanent::Zero, "0"),
+    (Permanent::ZeroPlus, "0+"),
+    (Permanent::One, "1"),
+    (Permanent::OnePlus, "1+"),
+    (Permanent::Two, "2"),
+];
+
+pub(crate) fn title(info: &Info) -> String {
+    let mut title = format!(
+        "Department {}, row {}, {}",
+        info.value,
+        info.row + 1,
+        match info.edition {
+            Edition::Base => "base game",
+            Edition::Expansion => "expansion",
         }
+    );
+    if info.permanent {
+        title.push_str(", permanent");
     }
+    title
 }
 
-#[derive(Store, PartialEq, Default, Deserialize, Serialize, Clone)]
-#[store(storage = "local")]
-struct State {
-    tiles: Tiles,
-    limit: LimitTypes,
-    permanent: Permanent,
+/// A button for each tile of the current tiles to toggle it in `set`.
+///
+/// Tiles of `set` which aren't part of the current tiles keep their button, to be able to remove
+/// them.
+fn toggles(
+    options: &Options,
+    name: &str,
+    class: &str,
+    set: BuildingSet,
+    toggle: impl Fn(Building) -> Callback<Event>,
+) -> Html {
+    let rows = (0..4).map(|row| {
+        let row = Building::all()
+            .filter(|b| b.row() == row && (b.is_tile(options.tiles) || set.contains(*b)))
+            .map(|b| {
+                let id = format!("{}{}", name, b.value());
+                let tile = b.is_tile(options.tiles);
+                let class = if tile { class } else { "btn-outline-secondary" };
+                html! {
+                    <td title={if tile {""} else {"Not one of the selected tiles"}}>
+                        <input
+                            type="checkbox"
+                            class="btn-check"
+                            id={id.clone()}
+                            autocomplete="off"
+                            checked={set.contains(b)}
+                            onchange={toggle(b)}
+                        />
+                        <label class={format!("btn btn-sm {}", class)} for={id}>{b.value()}</label>
+                    </td>
+                }
+            });
+        html! {<tr>{for row}</tr>}
+    });
+    html! {
+        <table align="center">
+            {for rows}
+        </table>
+    }
 }
 
 #[function_component]
 pub(crate) fn BuildingsPane(props: &Props) -> Html {
     let data = props.data.borrow();
-    let (state, dispatch) = use_store::<State>();
+    let options = data.options;
+    let set = |f: fn(&mut Options)| {
+        let on_change = props.on_change.clone();
+        Callback::from(move |_: Event| {
+            let mut options = options;
+            f(&mut options);
+            on_change.emit(options);
+        })
+    };
 
-    let set_base = dispatch.reduce_mut_callback(|state| state.tiles = Tiles::Base);
-    let set_both = dispatch.reduce_mut_callback(|state| state.tiles = Tiles::Both);
-    let set_expansion = dispatch.reduce_mut_callback(|state| state.tiles = Tiles::Expansion);
+    let toggle_excluded = |b: Building| {
+        let on_change = props.on_change.clone();
+        Callback::from(move |_: Event| {
+            let mut options = options;
+            options.excluded.toggle(b);
+            on_change.emit(options);
+        })
+    };
+    let toggle_required = |b: Building| {
+        let on_change = props.on_change.clone();
+        Callback::from(move |_: Event| {
+            let mut options = options;
+            options.required.toggle(b);
+            on_change.emit(options);
+        })
+    };
 
-    let mut bs = vec![BTreeMap::new(); 4];
-    let mut count = 0;
-    let limit = state.limit as u8 as usize;
-    let blue_min = state.permanent.min();
-    let blue_max = state.permanent.max();
-    let mut blues_missing = blue_min * 4;
-    for b in data
-        .buildings
-        .0
-        .iter()
-        .copied()
-        .filter(|b| b.is_tile(state.tiles))
-    {
-        let b_is_blue = b.blue();
-        let row = &mut bs[b.row()];
-        let row_types = row.len();
-        let row_len = row.values().map(|x: &Vec<usize>| x.len()).sum::<usize>();
-        let row_blue = row.keys().copied().filter(Building::blue).count();
-        if row_len < 8 {
-            if let Some(e) = row.get_mut(&b) {
-                if count == 32 - blues_missing {
-                    continue;
-                }
-                if row_len - row_blue + blue_min >= 8 {
-                    continue;
+    let per_row = use_state(|| false);
+    let editing = use_state(|| None::<(usize, Building)>);
+    let per_row_shown =
+        *per_row || options.limit.same().is_none() || options.permanent.same().is_none();
+    let toggle_per_row = {
+        let per_row = per_row.clone();
+        let on_change = props.on_change.clone();
+        Callback::from(move |_: Event| {
+            if per_row_shown {
+                let mut options = options;
+                options.limit = PerRow::all(options.limit[0]);
+                options.permanent = PerRow::all(options.permanent[0]);
+                on_change.emit(options);
+            }
+            per_row.set(!per_row_shown);
+        })
+    };
+    let set_row = |row: usize, f: fn(&mut Options, usize, &str)| {
+        let on_change = props.on_change.clone();
+        Callback::from(move |e: Event| {
+            let value = e.target_unchecked_into::<HtmlSelectElement>().value();
+            let mut options = options;
+            f(&mut options, row, &value);
+            on_change.emit(options);
+        })
+    };
+    let rows = (0..4).map(|row| {
+        html! {
+            <tr>
+                <td>{format!("Row {}", row + 1)}</td>
+                <td>
+                    <select
+                        class="form-select form-select-sm"
+                        onchange={set_row(row, |options, row, value| {
+                            options.limit[row] = value.parse().unwrap_or(options.limit[row])
+                        })}
+                    >
+                        {for LIMITS.iter().filter(|(limit, _)| options.tiles.limits().contains(limit)).map(|(limit, label)| html! {
+                            <option value={limit.to_string()} selected={options.limit[row] == *limit}>{label}</option>
+                        })}
+                    </select>
+                </td>
+                <td>
+                    <select
+                        class="form-select form-select-sm"
+                        onchange={set_row(row, |options, row, value| {
+                            options.permanent[row] = value.parse().unwrap_or(options.permanent[row])
+                        })}
+                    >
+                        {for PERMANENT.iter().filter(|(permanent, _)| options.tiles.permanents().contains(permanent)).map(|(permanent, label)| html! {
+                            <option value={permanent.to_string()} selected={options.permanent[row] == *permanent}>{label}</option>
+                        })}
+                    </select>
+                </td>
+            </tr>
+        }
+    });
+
+    let rule_first = use_node_ref();
+    let rule_kind = use_node_ref();
+    let rule_second = use_node_ref();
+    let add_rule = {
+        let on_change = props.on_change.clone();
+        let rule_first = rule_first.clone();
+        let rule_kind = rule_kind.clone();
+        let rule_second = rule_second.clone();
+        Callback::from(move |_: MouseEvent| {
+            let value = |r: &NodeRef| r.cast::<HtmlSelectElement>().unwrap().value();
+            let building = |r: &NodeRef| value(r).parse().ok().and_then(Building::from_value);
+            if let (Some(first), Some(second)) = (building(&rule_first), building(&rule_second)) {
+                let mut options = options;
+                if value(&rule_kind) == "forbidden" {
+                    options.rules.forbidden.insert(first, second);
+                } else {
+                    options.rules.required.insert(first, second);
                 }
-                e.push(count);
+                on_change.emit(options);
+            }
+        })
+    };
+    let remove_rule = |forbidden: bool, first: Building, second: Building| {
+        let on_change = props.on_change.clone();
+        Callback::from(move |_: MouseEvent| {
+            let mut options = options;
+            if forbidden {
+                options.rules.forbidden.remove(first, second);
             } else {
-                if row_types == limit {
-                    continue;
-                }
-                if b_is_blue {
-                    if row_blue == blue_max {
-                        continue;
-                    }
-                    if row_blue < blue_min && blues_missing > 0 {
-                        blues_missing -= 1;
-                    }
-                } else {
-                    if count == 32 - blues_missing {
-                        continue;
-                    }
-                    if row_types - row_blue + blue_min >= limit {
-                        continue;
-                    }
-                    if row_len - row_blue + blue_min >= 8 {
-                        continue;
+                options.rules.required.remove(first, second);
+            }
+            on_change.emit(options);
+        })
+    };
+    let rules = options
+        .rules
+        .forbidden
+        .iter()
+        .map(|(first, second)| (true, first, second))
+        .chain(
+            options
+                .rules
+                .required
+                .iter()
+                .map(|(first, second)| (false, first, second)),
+        )
+        .map(|(forbidden, first, second)| {
+            html! {
+                <li>
+                    if forbidden {
+                        {format!("Never {} with {} ", first.value(), second.value())}
+                    } else {
+                        {format!("{} requires {} ", first.value(), second.value())}
                     }
-                }
+                    <Button style={Color::Secondary} size={ButtonSize::Small} onclick={remove_rule(forbidden, first, second)}>
+                        {BI::TRASH}
+                    </Button>
+                </li>
+            }
+        });
+    let tile_options = || {
+        Building::all()
+            .filter(|b| b.is_tile(options.tiles))
+            .map(|b| html! {<option value={b.value().to_string()}>{b.value()}</option>})
+    };
 
-                row.insert(b, vec![count]);
+    let set_base = set(|options| {
+        options.tiles = Tiles::Base;
+        options.clamp();
+    });
+    let set_both = set(|options| {
+        options.tiles = Tiles::Both;
+        options.clamp();
+    });
+    let set_expansion = set(|options| {
+        options.tiles = Tiles::Expansion;
+        options.clamp();
+    });
+
+    let limits = LIMITS
+        .iter()
+        .enumerate()
+        .filter(|(_, (limit, _))| options.tiles.limits().contains(limit))
+        .map(|(i, (limit, label))| {
+            let limit = *limit;
+            let on_change = props.on_change.clone();
+            let id = format!("limit{}", i);
+            html! {
+                <>
+                <input
+                    type="radio"
+                    class="btn-check"
+                    name="limit"
+                    id={id.clone()}
+                    autocomplete="off"
+                    checked={options.limit == PerRow::all(limit)}
+                    onchange={Callback::from(move |_: Event| on_change.emit(Options { limit: PerRow::all(limit), ..options }))}
+                />
+                <label class="btn btn-outline-primary" for={id}>{label}</label>
+                </>
             }
-            count += 1;
-            if count == 32 {
-                break;
+        });
+    let permanents = PERMANENT
+        .iter()
+        .enumerate()
+        .filter(|(_, (permanent, _))| options.tiles.permanents().contains(permanent))
+        .map(|(i, (permanent, label))| {
+            let permanent = *permanent;
+            let on_change = props.on_change.clone();
+            let id = format!("permanent{}", i);
+            html! {
+                <>
+                <input
+                    type="radio"
+                    class="btn-check"
+                    name="permanent"
+                    id={id.clone()}
+                    autocomplete="off"
+                    checked={options.permanent == PerRow::all(permanent)}
+                    onchange={Callback::from(move |_: Event| on_change.emit(Options { permanent: PerRow::all(permanent), ..options }))}
+                />
+                <label class="btn btn-outline-primary" for={id}>{label}</label>
+                </>
             }
-        }
-    }
+        });
 
-    let buildings = match data.players {
-        Players::All => 32,
-        Players::Four => 7,
-        Players::Three => 3,
-        Players::Two => 1,
+    let departments = match &data.setup {
+        Ok(setup) => &setup.departments[..],
+        Err(_) => &[],
     };
-
-    let bs = bs.iter().map(|rx| {
-        let rx = rx.iter().map(|(cx, cc)| {
-            let blue =cx.blue();
-            let cc = cc.iter().filter(|x|**x&buildings!=buildings).count();
-            if cc > 0 {
-                let cc = if cc == 1 {BI::LAYERS_HALF}else{BI::LAYERS_FILL};
+    // Only locked rows and setups from a code can differ from the options, e.g. after editing them
+    let (violations, row_violations): (Vec<Violation>, [Vec<Violation>; 4]) = match &data.setup {
+        Ok(setup) if !data.locked.is_empty() || data.from_code => {
+            let grid = setup.grid();
+            (
+                validate::layout(&grid, &options),
+                [0, 1, 2, 3].map(|row| validate::row(row, &grid[row], &options)),
+            )
+        }
+        _ => Default::default(),
+    };
+    let bs = departments.iter().enumerate().map(|(row, rx)| {
+        let rx = rx.iter().map(|d| {
+            let blue = d.building.blue();
+            if d.copies > 0 {
+                let cc = if d.copies == 1 {BI::LAYERS_HALF}else{BI::LAYERS_FILL};
+                let info = d.building.info();
+                let edit = {
+                    let editing = editing.clone();
+                    let building = d.building;
+                    Callback::from(move |_: MouseEvent| editing.set(Some((row, building))))
+                };
                 html! {
-                <td><small>{cc}{" "}</small><span style={if blue {"color: blue"}else{""}}>{cx.value()}</span></td>
+                <td title={title(info)} style="cursor: pointer" onclick={edit}>
+                    <small>{cc}{" "}</small><span style={if blue {"color: blue"}else{""}}>{d.building.value()}</span>
+                </td>
             }
             }else{
                 html!{<td/>}
             }
         });
-        html! {<tr>{for rx}</tr>}
+        let locked = data.locked.0[row].is_some();
+        let on_lock = props.on_lock.clone();
+        html! {
+            <tr>
+                // A setup from a code has no seed which could change the other rows
+                if !data.from_code {
+                    <td>
+                        <Button
+                            style={if locked {Color::Primary} else {Color::Secondary}}
+                            size={ButtonSize::Small}
+                            onclick={Callback::from(move |_| on_lock.emit(row))}
+                        >
+                            {if locked {BI::LOCK_FILL} else {BI::UNLOCK}}
+                        </Button>
+                    </td>
+                }
+                {for rx}
+                if !row_violations[row].is_empty() {
+                    <td class="text-warning" title={row_violations[row].iter().map(ToString::to_string).collect::<Vec<_>>().join("\n")}>
+                        {BI::EXCLAMATION_TRIANGLE_FILL}
+                    </td>
+                }
+            </tr>
+        }
+    });
+    let replacements = (*editing).map(|(row, from)| {
+        // A department which is already in the row would get more than two copies
+        let replacements = Building::all()
+            .filter(|b| {
+                b.row() == row
+                    && !departments
+                        .get(row)
+                        .is_some_and(|r| r.iter().any(|d| d.building == *b))
+            })
+            .map(|to| {
+                let on_replace = props.on_replace.clone();
+                let editing = editing.clone();
+                html! {
+                    <>
+                    {" "}
+                    <Button
+                        style={if to.is_tile(options.tiles) {Color::Primary} else {Color::Secondary}}
+                        size={ButtonSize::Small}
+                        onclick={Callback::from(move |_| {
+                            editing.set(None);
+                            on_replace.emit((row, from, to));
+                        })}
+                    >
+                        {to.value()}
+                    </Button>
+                    </>
+                }
+            });
+        let editing = editing.clone();
+        html! {
+            <div>
+                {format!("Replace department {} with:", from.value())}
+                {for replacements}
+                {" "}
+                <Button style={Color::Secondary} size={ButtonSize::Small} onclick={Callback::from(move |_| editing.set(None))}>
+                    {BI::X}
+                </Button>
+            </div>
+        }
     });
 
     html! {
@@ -251,7 +471,7 @@ pub(crate) fn BuildingsPane(props: &Props) -> Html {
                     name="tiles"
                     id="tiles0"
                     autocomplete="off"
-                    checked={state.tiles == Tiles::Base}
+                    checked={options.tiles == Tiles::Base}
                     onchange={set_base}
                 />
                 <label class="btn btn-outline-primary" for="tiles0">{"Base"}</label>
@@ -262,7 +482,7 @@ pub(crate) fn BuildingsPane(props: &Props) -> Html {
                     name="tiles"
                     id="tiles1"
                     autocomplete="off"
-                    checked={state.tiles == Tiles::Both}
+                    checked={options.tiles == Tiles::Both}
                     onchange={set_both}
                 />
                 <label class="btn btn-outline-primary" for="tiles1">{"Base+Expansion"}</label>
@@ -273,124 +493,250 @@ pub(crate) fn BuildingsPane(props: &Props) -> Html {
                     name="tiles"
                     id="tiles2"
                     autocomplete="off"
-                    checked={state.tiles == Tiles::Expansion}
+                    checked={options.tiles == Tiles::Expansion}
                     onchange={set_expansion}
                 />
                 <label class="btn btn-outline-primary" for="tiles2">{"Expansion"}</label>
             </div>
         </div>
-        if state.tiles == Tiles::Both {
+        <div class="text-body-secondary">
+            <small>
+                {match options.tiles {
+                    Tiles::Base => "With the base game tiles every row has all 4 departments of the row, one of them permanent.",
+                    Tiles::Both => "With both every row has up to 8 different departments, up to 2 of them permanent.",
+                    Tiles::Expansion => "With the expansion tiles every row has all 4 departments of the row, one of them permanent.",
+                }}
+            </small>
+        </div>
+        if per_row_shown {
+            <table align="center">
+                <tr><th/><th>{"Different Departments"}</th><th>{"Permanent Departments"}</th></tr>
+                {for rows}
+            </table>
+        } else {
             <div>
                 {"Different Departments per row: "}
+                <div class="btn-group" role="group">
+                    {for limits}
+                </div>
+            </div>
+            <div>
+                {"Permanent Departments per row: "}
+                <div class="btn-group" role="group">
+                    {for permanents}
+                </div>
+            </div>
+        }
+        if options.tiles == Tiles::Both || per_row_shown {
+            <div class="form-check form-switch d-inline-block">
+                <input
+                    class="form-check-input"
+                    type="checkbox"
+                    role="switch"
+                    id="per_row"
+                    checked={per_row_shown}
+                    onchange={toggle_per_row}
+                />
+                <label class="form-check-label" for="per_row">{"Different settings per row"}</label>
+            </div>
+        }
+        if options.tiles == Tiles::Both {
+            <div>
+                {"Expansion Departments per row: "}
                 <div class="btn-group" role="group">
                     <input
                         type="radio"
                         class="btn-check"
-                        name="limit"
-                        id="limit0"
-                        autocomplete="off"
-                        checked={state.limit == LimitTypes::Four}
-                        onchange={dispatch.reduce_mut_callback(|state|state.limit=LimitTypes::Four)}
-                    />
-                    <label class="btn btn-outline-primary" for="limit0">{"4"}</label>
-
-                    <input
-                        type="radio"
-                        class="btn-check"
-                        name="limit"
-                        id="limit1"
-                        autocomplete="off"
-                        checked={state.limit == LimitTypes::Five}
-                        onchange={dispatch.reduce_mut_callback(|state|state.limit=LimitTypes::Five)}
-                    />
-                    <label class="btn btn-outline-primary" for="limit1">{"up to 5"}</label>
-
-                    <input
-                        type="radio"
-                        class="btn-check"
-                        name="limit"
-                        id="limit2"
+                        name="expansion"
+                        id="expansion0"
                         autocomplete="off"
-                        checked={state.limit == LimitTypes::Six}
-                        onchange={dispatch.reduce_mut_callback(|state|state.limit=LimitTypes::Six)}
+                        checked={options.expansion == Expansion::Any}
+                        onchange={set(|options| options.expansion = Expansion::Any)}
                     />
-                    <label class="btn btn-outline-primary" for="limit2">{"up to 6"}</label>
+                    <label class="btn btn-outline-primary" for="expansion0">{"any"}</label>
 
                     <input
                         type="radio"
                         class="btn-check"
-                        name="limit"
-                        id="limit3"
-                        autocomplete="off"
-                        checked={state.limit == LimitTypes::All}
-                        onchange={dispatch.reduce_mut_callback(|state|state.limit=LimitTypes::All)}
-                    />
-                    <label class="btn btn-outline-primary" for="limit3">{"up to 8"}</label>
-                </div>
-            </div>
-            <div>
-                {"Permanent Departments per row: "}
-                <div class="btn-group" role="group">
-                    <input
-                        type="radio"
-                        class="btn-check"
-                        name="permanent"
-                        id="permanent0"
+                        name="expansion"
+                        id="expansion1p"
                         autocomplete="off"
-                        checked={state.permanent == Permanent::Zero}
-                        onchange={dispatch.reduce_mut_callback(|state|state.permanent=Permanent::Zero)}
+                        checked={options.expansion == Expansion::OnePlus}
+                        onchange={set(|options| options.expansion = Expansion::OnePlus)}
                     />
-                    <label class="btn btn-outline-primary" for="permanent0">{"0"}</label>
+                    <label class="btn btn-outline-primary" for="expansion1p">{"1+"}</label>
 
                     <input
                         type="radio"
                         class="btn-check"
-                        name="permanent"
-                        id="permanent0p"
+                        name="expansion"
+                        id="expansion1"
                         autocomplete="off"
-                        checked={state.permanent == Permanent::ZeroPlus}
-                        onchange={dispatch.reduce_mut_callback(|state|state.permanent=Permanent::ZeroPlus)}
+                        checked={options.expansion == Expansion::One}
+                        onchange={set(|options| options.expansion = Expansion::One)}
                     />
-                    <label class="btn btn-outline-primary" for="permanent0p">{"0+"}</label>
+                    <label class="btn btn-outline-primary" for="expansion1">{"1"}</label>
 
                     <input
                         type="radio"
                         class="btn-check"
-                        name="permanent"
-                        id="permanent1"
+                        name="expansion"
+                        id="expansion2"
                         autocomplete="off"
-                        checked={state.permanent == Permanent::One}
-                        onchange={dispatch.reduce_mut_callback(|state|state.permanent=Permanent::One)}
+                        checked={options.expansion == Expansion::Two}
+                        onchange={set(|options| options.expansion = Expansion::Two)}
                     />
-                    <label class="btn btn-outline-primary" for="permanent1">{"1"}</label>
+                    <label class="btn btn-outline-primary" for="expansion2">{"2"}</label>
 
                     <input
                         type="radio"
                         class="btn-check"
-                        name="permanent"
-                        id="permanent2"
+                        name="expansion"
+                        id="expansion3"
                         autocomplete="off"
-                        checked={state.permanent == Permanent::OnePlus}
-                        onchange={dispatch.reduce_mut_callback(|state|state.permanent=Permanent::OnePlus)}
+                        checked={options.expansion == Expansion::Three}
+                        onchange={set(|options| options.expansion = Expansion::Three)}
                     />
-                    <label class="btn btn-outline-primary" for="permanent2">{"1+"}</label>
+                    <label class="btn btn-outline-primary" for="expansion3">{"3"}</label>
 
                     <input
                         type="radio"
                         class="btn-check"
-                        name="permanent"
-                        id="permanent3"
+                        name="expansion"
+                        id="expansion4"
                         autocomplete="off"
-                        checked={state.permanent == Permanent::Two}
-                        onchange={dispatch.reduce_mut_callback(|state|state.permanent=Permanent::Two)}
+                        checked={options.expansion == Expansion::Four}
+                        onchange={set(|options| options.expansion = Expansion::Four)}
                     />
-                    <label class="btn btn-outline-primary" for="permanent3">{"2"}</label>
+                    <label class="btn btn-outline-primary" for="expansion4">{"4"}</label>
                 </div>
             </div>
         }
+        <div>
+            {"Selection: "}
+            <div class="btn-group" role="group">
+                <input
+                    type="radio"
+                    class="btn-check"
+                    name="sampling"
+                    id="sampling0"
+                    autocomplete="off"
+                    checked={options.sampling == Sampling::Draw}
+                    onchange={set(|options| options.sampling = Sampling::Draw)}
+                />
+                <label class="btn btn-outline-primary" for="sampling0">{"Draw tiles"}</label>
+
+                <input
+                    type="radio"
+                    class="btn-check"
+                    name="sampling"
+                    id="sampling1"
+                    autocomplete="off"
+                    checked={options.sampling == Sampling::Uniform}
+                    onchange={set(|options| options.sampling = Sampling::Uniform)}
+                />
+                <label class="btn btn-outline-primary" for="sampling1">{"Uniform"}</label>
+            </div>
+        </div>
+        <details>
+            <summary>
+                {"Excluded departments"}
+                if !options.excluded.is_empty() {
+                    {format!(" ({})", options.excluded.iter().count())}
+                }
+            </summary>
+            {toggles(&options, "excluded", "btn-outline-danger", options.excluded, toggle_excluded)}
+        </details>
+        <details>
+            <summary>
+                {"Required departments"}
+                if !options.required.is_empty() {
+                    {format!(" ({})", options.required.iter().count())}
+                }
+            </summary>
+            {toggles(&options, "required", "btn-outline-success", options.required, toggle_required)}
+        </details>
+        <details>
+            <summary>
+                {"Rules"}
+                if !options.rules.is_empty() {
+                    {format!(" ({})", options.rules.forbidden.iter().count() + options.rules.required.iter().count())}
+                }
+            </summary>
+            <ul class="list-unstyled">
+                {for rules}
+            </ul>
+            <div class="input-group input-group-sm justify-content-center">
+                <select class="form-select flex-grow-0 w-auto" ref={rule_first}>
+                    {for tile_options()}
+                </select>
+                <select class="form-select flex-grow-0 w-auto" ref={rule_kind}>
+                    <option value="forbidden">{"never with"}</option>
+                    <option value="required">{"requires"}</option>
+                </select>
+                <select class="form-select flex-grow-0 w-auto" ref={rule_second}>
+                    {for tile_options()}
+                </select>
+                <Button size={ButtonSize::Small} onclick={add_rule}>
+                    {BI::PLUS}{" Add"}
+                </Button>
+            </div>
+        </details>
+        if let Err(err) = &data.setup {
+            <div class="alert alert-danger mb-0" role="alert">
+                {format!("These settings can't produce a complete department grid: impossible to fill row {}.", err.rows.iter().map(|row| (row + 1).to_string()).collect::<Vec<_>>().join(", "))}
+                if !options.excluded.is_empty() || !options.required.is_empty() {
+                    {" Try to exclude or require fewer departments."}
+                }
+                if !options.rules.is_empty() {
+                    {" Try to remove some rules."}
+                }
+                if !data.locked.is_empty() {
+                    {" Try to unlock rows:"}
+                    {for (0..4).filter(|row| data.locked.0[*row].is_some()).map(|row| {
+                        let on_lock = props.on_lock.clone();
+                        html! {
+                            <>
+                            {" "}
+                            <Button size={ButtonSize::Small} onclick={Callback::from(move |_| on_lock.emit(row))}>
+                                {BI::LOCK_FILL}{format!(" Row {}", row + 1)}
+                            </Button>
+                            </>
+                        }
+                    })}
+                }
+            </div>
+        }
         <table align="center">
             {for bs}
         </table>
+        {for replacements}
+        if !violations.is_empty() {
+            <div class="alert alert-warning mb-0" role="alert">
+                {if data.from_code {"The setup doesn't match the settings:"} else {"The locked rows don't match the settings:"}}
+                <ul class="mb-0">
+                    {for violations.iter().map(|v| html! {<li>{v.to_string()}</li>})}
+                </ul>
+            </div>
+        }
+        if let Ok(setup) = &data.setup {
+            <details>
+                <summary>{format!("Pull list ({} tiles)", setup.pull_list().tiles())}</summary>
+                <table class="table table-sm w-auto mx-auto">
+                    <tbody>
+                        {for setup.pull_list().0.iter().map(|d| html! {
+                            <tr title={title(d.building.info())}>
+                                <td style={if d.building.blue() {"color: blue"} else {""}}>{d.building.value()}</td>
+                                <td>{format!("× {}", d.copies)}</td>
+                            </tr>
+                        })}
+                    </tbody>
+                </table>
+            </details>
+        }
+        if let Some(layouts) = data.layouts {
+            <div class="text-body-secondary"><small>{format!("{} possible department grids", layouts)}</small></div>
+        }
         </>
     }
 }
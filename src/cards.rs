@@ -0,0 +1,311 @@
+use crate::options::Players;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// The setup cards, in the order they are drawn.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Cards(pub(crate) Vec<u8>);
+
+impl Default for Cards {
+    fn default() -> Self {
+        Self((0..20).collect())
+    }
+}
+
+impl Cards {
+    /// The cards (indices in [`CARDS`]) in the order they were drawn, e.g. the real ones.
+    ///
+    /// The cards are used until all disks are placed, so the order doesn't need to be complete.
+    pub fn new(order: Vec<u8>) -> Self {
+        Self(order)
+    }
+
+    /// Places the disks of the setup cards, in draw order, until all are used.
+    pub fn block(&self, players: Players) -> Blocked {
+        let mut disks = players.disks();
+
+        let mut donations = [[false; 4]; 5];
+        let mut cities = BTreeMap::new();
+        let mut drawn = Vec::new();
+
+        if disks > 0 {
+            'outer: for idx in self.0.iter().cloned() {
+                drawn.push(Drawn {
+                    card: idx,
+                    donation: false,
+                    cities: Vec::new(),
+                });
+                let card = drawn.last_mut().unwrap();
+                let (y, x) = card.space();
+                if !donations[y][x] {
+                    donations[y][x] = true;
+                    card.donation = true;
+                    disks -= 1;
+                    if disks == 0 {
+                        break;
+                    }
+                }
+                for c in card.all_cities().iter().copied() {
+                    let ce = cities.entry(c).or_insert(0);
+                    if *ce < c.spaces() {
+                        *ce += 1;
+                        card.cities.push(c);
+                        disks -= 1;
+                        if disks == 0 {
+                            break 'outer;
+                        }
+                    }
+                }
+            }
+        }
+
+        Blocked {
+            donations,
+            cities,
+            drawn,
+        }
+    }
+}
+
+/// The blocked donation spaces (5 rows of 4) and the number of blocked spaces per city.
+#[derive(Clone, Default, Eq, PartialEq, Debug)]
+pub struct Blocked {
+    pub donations: [[bool; 4]; 5],
+    pub cities: BTreeMap<City, usize>,
+    /// The cards which were used, in draw order.
+    pub drawn: Vec<Drawn>,
+}
+
+/// A drawn setup card and the spaces which got a disk from it.
+#[derive(Clone, Eq, PartialEq, Debug, Serialize)]
+pub struct Drawn {
+    /// The index of the card in [`CARDS`].
+    pub card: u8,
+    /// Whether the donation space got a disk (it may already be blocked or the disks ran out).
+    pub donation: bool,
+    /// The cities which got a disk, the others are full or the disks ran out.
+    pub cities: Vec<City>,
+}
+
+impl Drawn {
+    /// The donation space of the card as (row, column).
+    pub fn space(&self) -> (usize, usize) {
+        (self.card as usize % 5, self.card as usize / 5)
+    }
+
+    /// All cities on the card.
+    pub fn all_cities(&self) -> &'static [City] {
+        CARDS[self.card as usize]
+    }
+}
+
+#[derive(Eq, PartialEq, Ord, PartialOrd, Copy, Clone, Debug, Serialize)]
+pub enum City {
+    // WEST
+    Boise,
+    Denver,     //3
+    LosAngeles, //3
+    Portland,
+    Reno,
+    SaltLakeCity,
+    SanFrancisco,
+    SantaFe,
+    // MIDWEST
+    Chicago,
+    Cincinnati,
+    Duluth,
+    Fargo,
+    KansasCity, //3
+    Omaha,
+    StLouis, //3
+    StPaul,
+    // EAST
+    Albany, //3
+    Boston, //3
+    NewYork,
+    Pittsburgh, //3
+    Washington, //3
+    // SOUTH
+    Atlanta, //3
+    Charleston,
+    Dallas,
+    Houston, //3
+    Memphis,
+    NewOrleans,
+    SanAntonio,
+}
+
+impl City {
+    /// All cities, in the order of the enum.
+    pub const ALL: [City; 28] = [
+        City::Boise,
+        City::Denver,
+        City::LosAngeles,
+        City::Portland,
+        City::Reno,
+        City::SaltLakeCity,
+        City::SanFrancisco,
+        City::SantaFe,
+        City::Chicago,
+        City::Cincinnati,
+        City::Duluth,
+        City::Fargo,
+        City::KansasCity,
+        City::Omaha,
+        City::StLouis,
+        City::StPaul,
+        City::Albany,
+        City::Boston,
+        City::NewYork,
+        City::Pittsburgh,
+        City::Washington,
+        City::Atlanta,
+        City::Charleston,
+        City::Dallas,
+        City::Houston,
+        City::Memphis,
+        City::NewOrleans,
+        City::SanAntonio,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            City::Boise => "Boise",
+            City::Denver => "Denver",
+            City::LosAngeles => "Los Angeles",
+            City::Portland => "Portland",
+            City::Reno => "Reno",
+            City::SaltLakeCity => "Salt Lake City",
+            City::SanFrancisco => "San Francisco",
+            City::SantaFe => "Santa Fe",
+            City::Chicago => "Chicago",
+            City::Cincinnati => "Cincinnati",
+            City::Duluth => "Duluth",
+            City::Fargo => "Fargo",
+            City::KansasCity => "Kansas City",
+            City::Omaha => "Omaha",
+            City::StLouis => "St Louis",
+            City::StPaul => "St Paul",
+            City::Albany => "Albany",
+            City::Boston => "Boston",
+            City::NewYork => "New York",
+            City::Pittsburgh => "Pittsburgh",
+            City::Washington => "Washington",
+            City::Atlanta => "Atlanta",
+            City::Charleston => "Charleston",
+            City::Dallas => "Dallas",
+            City::Houston => "Houston",
+            City::Memphis => "Memphis",
+            City::NewOrleans => "New Orleans",
+            City::SanAntonio => "San Antonio",
+        }
+    }
+
+    /// Number of spaces which can be blocked.
+    pub fn spaces(self) -> usize {
+        match self {
+            City::NewYork | City::Chicago | City::NewOrleans | City::SanFrancisco => 5,
+
+            City::Albany
+            | City::Boston
+            | City::Pittsburgh
+            | City::Washington
+            | City::KansasCity
+            | City::StLouis
+            | City::Atlanta
+            | City::Houston
+            | City::Denver
+            | City::LosAngeles => 3,
+
+            City::Cincinnati
+            | City::Duluth
+            | City::Fargo
+            | City::Omaha
+            | City::StPaul
+            | City::Charleston
+            | City::Dallas
+            | City::Memphis
+            | City::SanAntonio
+            | City::Boise
+            | City::Portland
+            | City::Reno
+            | City::SaltLakeCity
+            | City::SantaFe => 1,
+        }
+    }
+
+    /// Name of the region, also used as the CSS class.
+    pub fn region(self) -> &'static str {
+        match self {
+            City::Boise
+            | City::Denver
+            | City::LosAngeles
+            | City::Portland
+            | City::Reno
+            | City::SaltLakeCity
+            | City::SanFrancisco
+            | City::SantaFe => "west",
+            City::Chicago
+            | City::Cincinnati
+            | City::Duluth
+            | City::Fargo
+            | City::KansasCity
+            | City::Omaha
+            | City::StLouis
+            | City::StPaul => "midwest",
+            City::Albany | City::Boston | City::NewYork | City::Pittsburgh | City::Washington => {
+                "east"
+            }
+            City::Atlanta
+            | City::Charleston
+            | City::Dallas
+            | City::Houston
+            | City::Memphis
+            | City::NewOrleans
+            | City::SanAntonio => "south",
+        }
+    }
+}
+
+pub static CARDS: [&[City]; 20] = [
+    &[City::SaltLakeCity, City::Reno],
+    &[City::StLouis, City::Chicago],
+    &[City::Boston, City::Washington],
+    &[City::NewOrleans, City::Houston],
+    &[City::SanFrancisco, City::LosAngeles],
+    &[
+        City::Cincinnati,
+        City::Duluth,
+        City::StLouis,
+        City::KansasCity,
+    ],
+    &[
+        City::Albany,
+        City::NewYork,
+        City::Washington,
+        City::Pittsburgh,
+    ],
+    &[City::NewOrleans, City::Atlanta],
+    &[City::Boston, City::NewYork],
+    &[City::Chicago, City::Omaha],
+    &[City::Fargo, City::StPaul],
+    &[City::Pittsburgh, City::NewYork],
+    &[City::SanAntonio, City::Memphis, City::Dallas],
+    &[City::Portland, City::Boise, City::Denver, City::LosAngeles],
+    &[
+        City::NewYork,
+        City::Chicago,
+        City::NewOrleans,
+        City::SanFrancisco,
+    ],
+    &[City::Pittsburgh, City::Boston, City::Albany],
+    &[City::SanFrancisco, City::SantaFe],
+    &[
+        City::NewOrleans,
+        City::Atlanta,
+        City::Houston,
+        City::Charleston,
+    ],
+    &[City::SanFrancisco, City::Denver],
+    &[City::KansasCity, City::Chicago],
+];
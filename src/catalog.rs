@@ -0,0 +1,217 @@
+//! The printed information of all department tiles.
+
+use serde::Serialize;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize)]
+pub enum Edition {
+    Base,
+    Expansion,
+}
+
+/// Everything known about a department tile.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize)]
+pub struct Info {
+    /// The number of the tile (1-32).
+    pub value: u8,
+    /// The row of the department board (0-3).
+    pub row: usize,
+    pub edition: Edition,
+    /// Whether the department is permanent (blue).
+    pub permanent: bool,
+}
+
+/// All department tiles, ordered by their number.
+pub static CATALOG: [Info; 32] = [
+    Info {
+        value: 1,
+        row: 0,
+        edition: Edition::Base,
+        permanent: false,
+    },
+    Info {
+        value: 2,
+        row: 0,
+        edition: Edition::Base,
+        permanent: false,
+    },
+    Info {
+        value: 3,
+        row: 0,
+        edition: Edition::Base,
+        permanent: false,
+    },
+    Info {
+        value: 4,
+        row: 0,
+        edition: Edition::Base,
+        permanent: true,
+    },
+    Info {
+        value: 5,
+        row: 1,
+        edition: Edition::Base,
+        permanent: false,
+    },
+    Info {
+        value: 6,
+        row: 1,
+        edition: Edition::Base,
+        permanent: false,
+    },
+    Info {
+        value: 7,
+        row: 1,
+        edition: Edition::Base,
+        permanent: false,
+    },
+    Info {
+        value: 8,
+        row: 1,
+        edition: Edition::Base,
+        permanent: true,
+    },
+    Info {
+        value: 9,
+        row: 2,
+        edition: Edition::Base,
+        permanent: false,
+    },
+    Info {
+        value: 10,
+        row: 2,
+        edition: Edition::Base,
+        permanent: false,
+    },
+    Info {
+        value: 11,
+        row: 2,
+        edition: Edition::Base,
+        permanent: false,
+    },
+    Info {
+        value: 12,
+        row: 2,
+        edition: Edition::Base,
+        permanent: true,
+    },
+    Info {
+        value: 13,
+        row: 3,
+        edition: Edition::Base,
+        permanent: false,
+    },
+    Info {
+        value: 14,
+        row: 3,
+        edition: Edition::Base,
+        permanent: false,
+    },
+    Info {
+        value: 15,
+        row: 3,
+        edition: Edition::Base,
+        permanent: false,
+    },
+    Info {
+        value: 16,
+        row: 3,
+        edition: Edition::Base,
+        permanent: true,
+    },
+    Info {
+        value: 17,
+        row: 0,
+        edition: Edition::Expansion,
+        permanent: false,
+    },
+    Info {
+        value: 18,
+        row: 0,
+        edition: Edition::Expansion,
+        permanent: false,
+    },
+    Info {
+        value: 19,
+        row: 0,
+        edition: Edition::Expansion,
+        permanent: true,
+    },
+    Info {
+        value: 20,
+        row: 0,
+        edition: Edition::Expansion,
+        permanent: false,
+    },
+    Info {
+        value: 21,
+        row: 1,
+        edition: Edition::Expansion,
+        permanent: false,
+    },
+    Info {
+        value: 22,
+        row: 1,
+        edition: Edition::Expansion,
+        permanent: false,
+    },
+    Info {
+        value: 23,
+        row: 1,
+        edition: Edition::Expansion,
+        permanent: true,
+    },
+    Info {
+        value: 24,
+        row: 1,
+        edition: Edition::Expansion,
+        permanent: false,
+    },
+    Info {
+        value: 25,
+        row: 2,
+        edition: Edition::Expansion,
+        permanent: false,
+    },
+    Info {
+        value: 26,
+        row: 2,
+        edition: Edition::Expansion,
+        permanent: false,
+    },
+    Info {
+        value: 27,
+        row: 2,
+        edition: Edition::Expansion,
+        permanent: true,
+    },
+    Info {
+        value: 28,
+        row: 2,
+        edition: Edition::Expansion,
+        permanent: false,
+    },
+    Info {
+        value: 29,
+        row: 3,
+        edition: Edition::Expansion,
+        permanent: false,
+    },
+    Info {
+        value: 30,
+        row: 3,
+        edition: Edition::Expansion,
+        permanent: false,
+    },
+    Info {
+        value: 31,
+        row: 3,
+        edition: Edition::Expansion,
+        permanent: true,
+    },
+    Info {
+        value: 32,
+        row: 3,
+        edition: Edition::Expansion,
+        permanent: false,
+    },
+];
@@ -0,0 +1,251 @@
+//! Setup codes, which contain a finished setup instead of the seed and options to generate it.
+//!
+//! A code is `departments.players`, `departments.players.donations.cities` (if disks are used)
+//! or `departments.all.cards` (for the blocked spaces of 3 and 2 players):
+//!
+//! - departments: the department in each of the 32 slots (draw order, which also defines the
+//!   player counts a copy is used with), one digit (`0-9a-v`) for the tile
+//!   numbers 1-32 or `_`
+//! - players: as in links
+//! - donations: 20 bits (row by row), as 4 digits with 5 bits each
+//! - cities: a digit for the city and the number of blocked spaces, for each blocked city
+//! - cards: a digit for each setup card in draw order, up to the last one used with 2 players
+//!
+//! As the result is stored directly, codes stay valid regardless of changes to the generator.
+
+use crate::cards::{Cards, City, CARDS};
+use crate::departments::{Building, Departments};
+use crate::options::Players;
+use crate::Setup;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter, Write};
+use std::str::FromStr;
+
+const DIGITS: &[u8; 32] = b"0123456789abcdefghijklmnopqrstuv";
+
+const WORDS: [&str; 64] = [
+    "apple", "bear", "bell", "boat", "bread", "brick", "cake", "camel", "candle", "cat", "chair",
+    "cloud", "coin", "corn", "crow", "desk", "dog", "drum", "duck", "eagle", "egg", "fish", "flag",
+    "fork", "fox", "frog", "ghost", "goat", "grape", "hat", "horse", "house", "jam", "key", "kite",
+    "lamp", "leaf", "lemon", "lion", "moon", "mouse", "nail", "nut", "owl", "pear", "pen", "pig",
+    "pipe", "queen", "rain", "ring", "rose", "salt", "ship", "shoe", "sock", "star", "sun", "tent",
+    "tiger", "train", "tree", "wolf", "zebra",
+];
+
+fn digit(value: usize) -> char {
+    DIGITS[value] as char
+}
+
+fn value(digit: char) -> Option<usize> {
+    DIGITS.iter().position(|d| *d as char == digit)
+}
+
+/// A setup and the player count it is shown for.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct SetupCode {
+    pub players: Players,
+    pub setup: Setup,
+}
+
+impl SetupCode {
+    /// Four words which only depend on what is shown: the used departments and their copies and
+    /// the blocked donations and cities (if disks are used or for 3 and 2 players with all).
+    ///
+    /// Two devices which show the same fingerprint (almost certainly) show the same setup, even if
+    /// the setup was created by different versions or options.
+    pub fn fingerprint(&self) -> String {
+        // FNV-1a, as the hashers of std may change between releases
+        let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+        let mut write = |byte: u8| {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        };
+        for row in &self.setup.departments {
+            for d in row.iter().filter(|d| d.copies > 0) {
+                write(d.building.value());
+                write(d.copies as u8);
+            }
+            write(0);
+        }
+        let mut blocked = Vec::new();
+        if self.players.disks() > 0 {
+            blocked.push((self.setup.donations, self.setup.cities.clone()));
+        } else if self.players == Players::All {
+            // The blocked spaces for 3 and 2 players are shown together
+            for players in [Players::Three, Players::Two] {
+                if let Some(b) = self.setup.blocked(players) {
+                    blocked.push((b.donations, b.cities));
+                }
+            }
+        }
+        for (donations, cities) in &blocked {
+            for d in donations.iter().flatten() {
+                write(u8::from(*d));
+            }
+            for (city, count) in cities {
+                write(City::ALL.iter().position(|c| c == city).unwrap() as u8);
+                write(*count as u8);
+            }
+        }
+        (0..4)
+            .map(|i| WORDS[(hash >> (i * 6)) as usize % 64])
+            .collect::<Vec<_>>()
+            .join("-")
+    }
+
+    /// Replaces all copies of `from` with `to`, which must be in the same row but not in it yet.
+    pub fn replace(&mut self, from: Building, to: Building) {
+        let rows = &mut self.setup.slots.0;
+        if from.row() != to.row() || rows[to.row()].contains_key(&to) {
+            return;
+        }
+        if let Some(slots) = rows[from.row()].remove(&from) {
+            rows[to.row()].insert(to, slots);
+        }
+        self.setup.departments = self.setup.slots.rows(self.players);
+    }
+}
+
+impl Display for SetupCode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut slots = [None; 32];
+        for (b, s) in self.setup.slots.0.iter().flatten() {
+            for s in s {
+                if let Some(slot) = slots.get_mut(*s) {
+                    *slot = Some(*b);
+                }
+            }
+        }
+        for slot in slots {
+            match slot {
+                Some(b) => f.write_char(digit(b.value() as usize - 1))?,
+                None => f.write_char('_')?,
+            }
+        }
+        write!(f, ".{}", self.players)?;
+
+        if self.players.disks() > 0 {
+            f.write_char('.')?;
+            let donations = self.setup.donations.iter().flatten().collect::<Vec<_>>();
+            for bits in donations.chunks(5) {
+                let bits = bits
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, b)| ***b)
+                    .map(|(i, _)| 1 << i)
+                    .sum::<usize>();
+                f.write_char(digit(bits))?;
+            }
+            f.write_char('.')?;
+            for (city, count) in &self.setup.cities {
+                let i = City::ALL.iter().position(|c| c == city).unwrap();
+                f.write_char(digit(i))?;
+                f.write_char(digit(*count))?;
+            }
+        } else if self.players == Players::All {
+            if let Some(blocked) = self.setup.blocked(Players::Two) {
+                f.write_char('.')?;
+                for drawn in &blocked.drawn {
+                    f.write_char(digit(drawn.card as usize))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for SetupCode {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split('.');
+        let departments = parts.next().ok_or(())?;
+        let players = parts.next().ok_or(())?.parse::<Players>()?;
+
+        let mut slots = Departments::default();
+        if departments.chars().count() != 32 {
+            return Err(());
+        }
+        for (s, c) in departments.chars().enumerate() {
+            if c != '_' {
+                let b = Building::from_value(value(c).ok_or(())? as u8 + 1).ok_or(())?;
+                slots.0[b.row()].entry(b).or_default().push(s);
+            }
+        }
+        // As there are 32 slots the rows are complete unless there are empty slots
+        if slots.0.iter().any(|row| {
+            row.values().any(|s| s.len() > 2) || row.values().map(Vec::len).sum::<usize>() > 8
+        }) {
+            return Err(());
+        }
+
+        let mut donations = [[false; 4]; 5];
+        let mut cities = BTreeMap::new();
+        if players.disks() > 0 {
+            let bits = parts.next().ok_or(())?.chars().collect::<Vec<_>>();
+            if bits.len() != 4 {
+                return Err(());
+            }
+            for (i, d) in donations.iter_mut().flatten().enumerate() {
+                *d = value(bits[i / 5]).ok_or(())? & (1 << (i % 5)) != 0;
+            }
+            let blocked = parts.next().ok_or(())?.chars().collect::<Vec<_>>();
+            for pair in blocked.chunks(2) {
+                let [city, count] = pair else {
+                    return Err(());
+                };
+                let city = *City::ALL.get(value(*city).ok_or(())?).ok_or(())?;
+                let count = value(*count).ok_or(())?;
+                if count == 0 || count > city.spaces() {
+                    return Err(());
+                }
+                if cities.insert(city, count).is_some() {
+                    return Err(());
+                }
+            }
+            let blocked =
+                donations.iter().flatten().filter(|d| **d).count() + cities.values().sum::<usize>();
+            if blocked != players.disks() {
+                return Err(());
+            }
+        }
+        let mut cards = None;
+        let order = match players {
+            Players::All => parts.next(),
+            _ => None,
+        };
+        if let Some(order) = order {
+            let order = order
+                .chars()
+                .map(|c| value(c).filter(|i| *i < CARDS.len()).map(|i| i as u8))
+                .collect::<Option<Vec<_>>>()
+                .ok_or(())?;
+            if (1..order.len()).any(|i| order[..i].contains(&order[i])) {
+                return Err(());
+            }
+            // The cards have to place all disks for 2 players, without any left over
+            let two = Cards::new(order.clone()).block(Players::Two);
+            let blocked = two.donations.iter().flatten().filter(|d| **d).count()
+                + two.cities.values().sum::<usize>();
+            if blocked != Players::Two.disks() || two.drawn.len() != order.len() {
+                return Err(());
+            }
+            cards = Some(Cards::new(order));
+        }
+        if parts.next().is_some() {
+            return Err(());
+        }
+
+        Ok(Self {
+            players,
+            setup: Setup {
+                departments: slots.rows(players),
+                donations,
+                cities,
+                drawn: Vec::new(),
+                slots,
+                cards,
+            },
+        })
+    }
+}
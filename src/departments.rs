@@ -0,0 +1,475 @@
+pub use crate::departments::building::Building;
+use crate::options::Players;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::ops::{BitAnd, BitOr, Not};
+use std::str::FromStr;
+
+mod building {
+    use crate::catalog::{Edition, Info, CATALOG};
+    use crate::options::Tiles;
+    use serde::Serialize;
+
+    #[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Debug, Serialize)]
+    pub struct Building(u8);
+
+    impl Building {
+        #[inline]
+        pub(crate) fn new(value: u8) -> Self {
+            Self(value)
+        }
+
+        /// Returns `None` if `value` is not a tile number (1-32).
+        #[inline]
+        pub fn from_value(value: u8) -> Option<Self> {
+            (1..=32).contains(&value).then_some(Self(value))
+        }
+
+        /// All department tiles, ordered by their number.
+        pub fn all() -> impl Iterator<Item = Self> {
+            (1..=32).map(Self)
+        }
+
+        /// The number printed on the tile (1-32).
+        #[inline]
+        pub fn value(&self) -> u8 {
+            self.0
+        }
+
+        #[inline]
+        pub fn info(&self) -> &'static Info {
+            &CATALOG[self.0 as usize - 1]
+        }
+
+        #[inline]
+        pub fn row(&self) -> usize {
+            self.info().row
+        }
+
+        /// Whether this is a permanent department.
+        #[inline]
+        pub fn blue(&self) -> bool {
+            self.info().permanent
+        }
+
+        #[inline]
+        pub fn is_tile(&self, tiles: Tiles) -> bool {
+            match tiles {
+                Tiles::Base => self.info().edition == Edition::Base,
+                Tiles::Both => true,
+                Tiles::Expansion => self.info().edition == Edition::Expansion,
+            }
+        }
+    }
+}
+
+/// A set of department tiles.
+#[derive(Copy, Clone, Default, Ord, PartialOrd, Eq, PartialEq, Debug, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct BuildingSet(u32);
+
+impl BuildingSet {
+    #[inline]
+    fn bit(building: Building) -> u32 {
+        1 << (building.value() - 1)
+    }
+
+    #[inline]
+    pub fn contains(&self, building: Building) -> bool {
+        self.0 & Self::bit(building) != 0
+    }
+
+    #[inline]
+    pub fn insert(&mut self, building: Building) {
+        self.0 |= Self::bit(building);
+    }
+
+    #[inline]
+    pub fn remove(&mut self, building: Building) {
+        self.0 &= !Self::bit(building);
+    }
+
+    #[inline]
+    pub fn toggle(&mut self, building: Building) {
+        self.0 ^= Self::bit(building);
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Building> + '_ {
+        Building::all().filter(|b| self.contains(*b))
+    }
+}
+
+impl BitAnd for BuildingSet {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl BitOr for BuildingSet {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl Not for BuildingSet {
+    type Output = Self;
+
+    fn not(self) -> Self::Output {
+        Self(!self.0)
+    }
+}
+
+/// The tile numbers, comma-separated.
+impl Display for BuildingSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, b) in self.iter().enumerate() {
+            if i != 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}", b.value())?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for BuildingSet {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut set = BuildingSet::default();
+        for value in s.split(',').filter(|v| !v.is_empty()) {
+            set.insert(
+                value
+                    .parse()
+                    .ok()
+                    .and_then(Building::from_value)
+                    .ok_or(())?,
+            );
+        }
+        Ok(set)
+    }
+}
+
+/// Ordered pairs of department tiles.
+#[derive(Copy, Clone, Default, Eq, PartialEq, Debug, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct Pairs([BuildingSet; 32]);
+
+impl Pairs {
+    #[inline]
+    pub fn contains(&self, first: Building, second: Building) -> bool {
+        self.0[first.value() as usize - 1].contains(second)
+    }
+
+    #[inline]
+    pub fn insert(&mut self, first: Building, second: Building) {
+        self.0[first.value() as usize - 1].insert(second);
+    }
+
+    #[inline]
+    pub fn remove(&mut self, first: Building, second: Building) {
+        self.0[first.value() as usize - 1].remove(second);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(BuildingSet::is_empty)
+    }
+
+    /// All pairs, ordered by the first and then the second tile.
+    pub fn iter(&self) -> impl Iterator<Item = (Building, Building)> + '_ {
+        Building::all().flat_map(|first| {
+            self.0[first.value() as usize - 1]
+                .iter()
+                .map(move |second| (first, second))
+        })
+    }
+}
+
+/// The pairs as `first-second`, comma-separated.
+impl Display for Pairs {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, (first, second)) in self.iter().enumerate() {
+            if i != 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}-{}", first.value(), second.value())?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Pairs {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let building = |v: &str| v.parse().ok().and_then(Building::from_value).ok_or(());
+        let mut pairs = Pairs::default();
+        for pair in s.split(',').filter(|v| !v.is_empty()) {
+            let (first, second) = pair.split_once('-').ok_or(())?;
+            pairs.insert(building(first)?, building(second)?);
+        }
+        Ok(pairs)
+    }
+}
+
+/// Both copies of all department tiles, in the order they are drawn.
+pub struct Buildings(pub(crate) Vec<Building>);
+
+impl Default for Buildings {
+    fn default() -> Self {
+        let mut b = Vec::with_capacity(64);
+        for i in 1..=32 {
+            b.push(Building::new(i));
+            b.push(Building::new(i));
+        }
+        Self(b)
+    }
+}
+
+/// The tiles to take out of the box: the used departments sorted by tile number, with their copies.
+#[derive(Clone, Default, Eq, PartialEq, Debug)]
+pub struct PullList(pub Vec<Department>);
+
+impl PullList {
+    /// The number of tiles to take.
+    pub fn tiles(&self) -> usize {
+        self.0.iter().map(|d| d.copies).sum()
+    }
+}
+
+/// The tile numbers with the number of copies, e.g. `01×2,03×1`.
+impl Display for PullList {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, d) in self.0.iter().enumerate() {
+            if i != 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{:02}×{}", d.building.value(), d.copies)?;
+        }
+        Ok(())
+    }
+}
+
+/// A department in the grid and how many copies of it are used.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize)]
+pub struct Department {
+    pub building: Building,
+    /// Zero if the department is only used with more players.
+    pub copies: usize,
+}
+
+/// A row which is kept as it is when the seed changes: the department and slot of all 8 copies.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct LockedRow(pub(crate) [(Building, u8); 8]);
+
+impl LockedRow {
+    /// Replaces all copies of `from` with `to`, unless `to` is already in the row.
+    pub fn replace(&mut self, from: Building, to: Building) {
+        if self.0.iter().any(|(b, _)| *b == to) {
+            return;
+        }
+        for (b, _) in &mut self.0 {
+            if *b == from {
+                *b = to;
+            }
+        }
+    }
+
+    /// The departments and how many copies of them are used.
+    pub(crate) fn counts(&self) -> BTreeMap<Building, u8> {
+        let mut counts = BTreeMap::new();
+        for (b, _) in self.0 {
+            *counts.entry(b).or_default() += 1;
+        }
+        counts
+    }
+}
+
+/// The copies as `department-slot`, comma-separated.
+impl Display for LockedRow {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, (b, slot)) in self.0.iter().enumerate() {
+            if i != 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}-{}", b.value(), slot)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for LockedRow {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let copies = s
+            .split(',')
+            .map(|copy| {
+                let (b, slot) = copy.split_once('-').ok_or(())?;
+                let b = b.parse().ok().and_then(Building::from_value).ok_or(())?;
+                let slot = slot.parse::<u8>().map_err(|_| ())?;
+                Ok((b, slot))
+            })
+            .collect::<Result<Vec<_>, ()>>()?;
+        Ok(Self(copies.try_into().map_err(|_| ())?))
+    }
+}
+
+/// The rows which are kept as they are when the seed changes.
+#[derive(Copy, Clone, Default, Eq, PartialEq, Debug)]
+pub struct Locked(pub [Option<LockedRow>; 4]);
+
+impl Locked {
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(Option::is_none)
+    }
+
+    /// Drops all rows with tiles from another row or slots which are out of range or already used
+    /// by a previous row.
+    pub(crate) fn checked(&self) -> Self {
+        let mut used = [false; 32];
+        let mut locked = Self::default();
+        for (row, lock) in self.0.iter().enumerate() {
+            let Some(lock) = lock else {
+                continue;
+            };
+            let mut slots = lock.0.map(|(_, slot)| slot as usize);
+            slots.sort();
+            if lock.0.iter().all(|(b, _)| b.row() == row)
+                && slots.windows(2).all(|w| w[0] != w[1])
+                && slots.iter().all(|s| *s < 32 && !used[*s])
+            {
+                for s in slots {
+                    used[s] = true;
+                }
+                locked.0[row] = Some(*lock);
+            }
+        }
+        locked
+    }
+}
+
+/// The selected departments per row, each with the slots (draw order) of its copies.
+#[derive(Clone, Default, Eq, PartialEq, Debug)]
+pub struct Departments(pub(crate) [BTreeMap<Building, Vec<usize>>; 4]);
+
+impl Departments {
+    pub fn rows(&self, players: Players) -> [Vec<Department>; 4] {
+        let mask = players.layer_mask();
+        self.0.clone().map(|row| {
+            row.into_iter()
+                .map(|(building, slots)| Department {
+                    building,
+                    copies: slots.into_iter().filter(|s| s & mask != mask).count(),
+                })
+                .collect()
+        })
+    }
+
+    /// The row as it is now, if it is complete.
+    pub(crate) fn lock(&self, row: usize) -> Option<LockedRow> {
+        let mut copies = self.0[row]
+            .iter()
+            .flat_map(|(b, slots)| slots.iter().map(|s| (*b, *s as u8)))
+            .collect::<Vec<_>>();
+        copies.sort_by_key(|(_, s)| *s);
+        copies.try_into().ok().map(LockedRow)
+    }
+
+    /// Replaces the locked rows with the locked copies.
+    ///
+    /// The other rows keep the order of their copies but get the slots which are not used by the
+    /// locked rows.
+    pub(crate) fn relock(&mut self, locked: &Locked) {
+        let mut free = [true; 32];
+        for lock in locked.0.iter().flatten() {
+            for (_, slot) in lock.0 {
+                free[slot as usize] = false;
+            }
+        }
+        let mut copies = self
+            .0
+            .iter()
+            .enumerate()
+            .filter(|(row, _)| locked.0[*row].is_none())
+            .flat_map(|(_, row)| {
+                row.iter()
+                    .flat_map(|(b, slots)| slots.iter().map(|s| (*s, *b)))
+            })
+            .collect::<Vec<_>>();
+        copies.sort();
+
+        let mut rows: [BTreeMap<Building, Vec<usize>>; 4] = Default::default();
+        for ((_, b), slot) in copies.into_iter().zip((0..32).filter(|s| free[*s])) {
+            rows[b.row()].entry(b).or_default().push(slot);
+        }
+        for lock in locked.0.iter().flatten() {
+            for (b, slot) in lock.0 {
+                rows[b.row()].entry(b).or_default().push(slot as usize);
+            }
+        }
+        self.0 = rows;
+    }
+
+    /// Moves one copy of each tile in `required` to a slot which is used with any player count,
+    /// swapping it with a tile which isn't required. Locked rows are not changed.
+    pub(crate) fn show(&mut self, required: BuildingSet, locked: &Locked) {
+        let mask = Players::Two.layer_mask();
+        for b in required.iter() {
+            if locked.0[b.row()].is_some() {
+                continue;
+            }
+            let Some(slots) = self.0[b.row()].get(&b) else {
+                continue;
+            };
+            if slots.iter().any(|s| s & mask != mask) {
+                continue;
+            }
+            let hidden = slots[0];
+            let other = self
+                .0
+                .iter_mut()
+                .enumerate()
+                .filter(|(row, _)| locked.0[*row].is_none())
+                .flat_map(|(_, row)| row.iter_mut())
+                .filter(|(o, _)| !required.contains(**o))
+                .flat_map(|(_, slots)| slots.iter_mut())
+                .filter(|s| **s & mask != mask)
+                .min_by_key(|s| **s);
+            if let Some(other) = other {
+                let visible = std::mem::replace(other, hidden);
+                self.0[b.row()].get_mut(&b).unwrap()[0] = visible;
+            }
+        }
+    }
+}
+
+/// The options make it impossible to fill all rows.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Infeasible {
+    /// The rows (0-3) which can't be filled.
+    pub rows: Vec<usize>,
+}
+
+impl Display for Infeasible {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "infeasible settings, impossible to fill row")?;
+        for (i, row) in self.rows.iter().enumerate() {
+            write!(f, "{}{}", if i == 0 { " " } else { ", " }, row + 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for Infeasible {}
@@ -0,0 +1,127 @@
+use crate::buildings::title;
+use crate::Data;
+use random_carnegie::departments::Building;
+use random_carnegie::manual::ManualDraw;
+use yew::{function_component, html, use_memo, use_state, Callback, Html, Properties};
+use yew_bootstrap::component::{Button, ButtonSize};
+use yew_bootstrap::icons::BI;
+use yew_bootstrap::util::Color;
+use yewdux::mrc::Mrc;
+
+#[derive(Properties, PartialEq)]
+pub(crate) struct Props {
+    pub data: Mrc<Data>,
+}
+
+#[function_component]
+pub(crate) fn DrawPane(props: &Props) -> Html {
+    let data = props.data.borrow();
+    let options = data.options;
+    // The tiles entered by hand, in draw order, if the grid is placed from them
+    let entered = use_state(|| None::<Vec<Building>>);
+    // Computing the valid layouts may take a while, so it is only done when the options change
+    let empty = use_memo(options, ManualDraw::new);
+
+    let toggle = {
+        let entered = entered.clone();
+        let active = entered.is_some();
+        Callback::from(move |_| entered.set(if active { None } else { Some(Vec::new()) }))
+    };
+
+    let content = entered.as_ref().map(|order| {
+        let mut draw = match &*empty {
+            Ok(draw) => draw.clone(),
+            Err(err) => return html! {<div class="alert alert-danger mb-0" role="alert">{err.to_string()}</div>},
+        };
+        let results = order.iter().map(|b| (*b, draw.place(*b))).collect::<Vec<_>>();
+
+        let buttons = (0..4).map(|row| {
+            let row = Building::all().filter(|b| b.row() == row).map(|b| {
+                let onclick = {
+                    let entered = entered.clone();
+                    let order = order.clone();
+                    Callback::from(move |_| {
+                        let mut order = order.clone();
+                        order.push(b);
+                        entered.set(Some(order));
+                    })
+                };
+                html! {
+                    <td>
+                        <Button
+                            style={if b.is_tile(options.tiles) {Color::Primary} else {Color::Secondary}}
+                            size={ButtonSize::Small}
+                            disabled={draw.is_complete()}
+                            {onclick}
+                        >
+                            {b.value()}
+                        </Button>
+                    </td>
+                }
+            });
+            html! {<tr>{for row}</tr>}
+        });
+        let undo = {
+            let entered = entered.clone();
+            let order = order.clone();
+            Callback::from(move |_| {
+                let mut order = order.clone();
+                order.pop();
+                entered.set(Some(order));
+            })
+        };
+        let log = results.iter().enumerate().rev().map(|(i, (b, result))| match result {
+            Ok(()) => html! {<li>{format!("{}. {} placed in row {}", i + 1, b.value(), b.row() + 1)}</li>},
+            Err(discard) => html! {<li class="text-body-secondary">{format!("{}. {} discarded: {}", i + 1, b.value(), discard)}</li>},
+        });
+        let grid = draw.rows(options.players).into_iter().map(|row| {
+            let row = row.into_iter().map(|d| {
+                if d.copies > 0 {
+                    let cc = if d.copies == 1 {BI::LAYERS_HALF} else {BI::LAYERS_FILL};
+                    html! {
+                        <td title={title(d.building.info())}>
+                            <small>{cc}{" "}</small><span style={if d.building.blue() {"color: blue"} else {""}}>{d.building.value()}</span>
+                        </td>
+                    }
+                } else {
+                    html! {<td/>}
+                }
+            });
+            html! {<tr>{for row}</tr>}
+        });
+
+        html! {
+            <>
+            <p>
+                {"Tap the department tiles in the order they were drawn, each one is placed if all rows can still be completed with it."}
+                if draw.is_complete() {
+                    {" "}<b>{"The grid is complete."}</b>
+                }
+            </p>
+            <table align="center">{for buttons}</table>
+            <div class="my-2">
+                <Button style={Color::Secondary} size={ButtonSize::Small} disabled={order.is_empty()} onclick={undo}>
+                    {BI::ARROW_COUNTERCLOCKWISE}{" Undo"}
+                </Button>
+            </div>
+            <table align="center">{for grid}</table>
+            <ul class="list-unstyled">{for log}</ul>
+            </>
+        }
+    });
+
+    html! {
+        <>
+        <div>
+            <Button style={Color::Secondary} size={ButtonSize::Small} onclick={toggle}>
+                if entered.is_some() {
+                    {"Hide the drawn department tiles"}
+                } else {
+                    {BI::PENCIL}{" Enter drawn department tiles"}
+                }
+            </Button>
+        </div>
+        {for content}
+        </>
+    }
+}
@@ -0,0 +1,112 @@
+//! The randomizer without any user interface.
+//!
+//! [`generate`] computes exactly the setup which the web application shows for a seed and options.
+
+use crate::cards::{Blocked, Cards, City, Drawn};
+use crate::departments::{Department, Departments, Infeasible, Locked, LockedRow, PullList};
+use crate::options::{Options, Players};
+use crate::seed::Seed;
+use crate::version::Version;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+pub mod cards;
+pub mod catalog;
+pub mod code;
+pub mod departments;
+pub mod link;
+pub mod manual;
+pub mod options;
+pub mod seed;
+mod solver;
+mod v1;
+mod v2;
+pub mod validate;
+pub mod version;
+
+/// A complete setup.
+#[derive(Clone, Default, Eq, PartialEq, Debug, Serialize)]
+pub struct Setup {
+    /// The four department rows, each sorted by tile number.
+    pub departments: [Vec<Department>; 4],
+    /// The blocked donation spaces (5 rows of 4).
+    pub donations: [[bool; 4]; 5],
+    /// The number of blocked spaces per city.
+    pub cities: BTreeMap<City, usize>,
+    /// The setup cards which were drawn to block the spaces (empty for a [`code::SetupCode`]).
+    pub drawn: Vec<Drawn>,
+    #[serde(skip)]
+    pub(crate) slots: Departments,
+    /// Unknown for a [`code::SetupCode`], unless it is for [`Players::All`].
+    #[serde(skip)]
+    pub(crate) cards: Option<Cards>,
+}
+
+impl Setup {
+    /// The department row as it is, to keep it when the seed changes (see [`Seed::locked`]).
+    ///
+    /// Returns `None` if the row is not complete.
+    pub fn lock(&self, row: usize) -> Option<LockedRow> {
+        self.slots.lock(row)
+    }
+
+    /// The used departments (for the player count of the setup) in the order of the box.
+    pub fn pull_list(&self) -> PullList {
+        let mut list = self
+            .departments
+            .iter()
+            .flatten()
+            .filter(|d| d.copies > 0)
+            .copied()
+            .collect::<Vec<_>>();
+        list.sort_by_key(|d| d.building);
+        PullList(list)
+    }
+
+    /// The blocked spaces for another player count, e.g. to show them with [`Players::All`].
+    ///
+    /// Returns `None` for a [`code::SetupCode`] for 4, 3 or 2 players, which only contains the
+    /// blocked spaces it was created for.
+    pub fn blocked(&self, players: Players) -> Option<Blocked> {
+        self.cards.as_ref().map(|cards| cards.block(players))
+    }
+
+    /// The department rows with the copies for all player counts.
+    pub fn grid(&self) -> [Vec<Department>; 4] {
+        self.slots.rows(Players::All)
+    }
+}
+
+/// Generates the setup for a seed.
+///
+/// Only [`Version::V1`] may return an incomplete grid instead of an error, it also ignores
+/// [`Seed::locked`].
+pub fn generate(seed: Seed, version: Version, options: &Options) -> Result<Setup, Infeasible> {
+    let (departments, cards) = match version {
+        Version::V1 => (
+            v1::departments(seed.departments, options),
+            v1::cards(seed.cards),
+        ),
+        Version::V2 => (
+            v2::departments(seed.departments, &seed.locked, options)?,
+            v2::cards(seed.cards),
+        ),
+    };
+    let blocked = cards.block(options.players);
+
+    Ok(Setup {
+        departments: departments.rows(options.players),
+        donations: blocked.donations,
+        cities: blocked.cities,
+        drawn: blocked.drawn,
+        slots: departments,
+        cards: Some(cards),
+    })
+}
+
+/// The number of different department grids which are valid for the options.
+///
+/// Which copies are only used with more players is not taken into account.
+pub fn count_layouts(options: &Options) -> Result<u64, Infeasible> {
+    Ok(solver::grid(options, &Locked::default())?.count())
+}
@@ -0,0 +1,107 @@
+use crate::options::{Expansion, Options};
+use crate::seed::Seed;
+use crate::version::Version;
+use std::fmt::{Display, Formatter};
+
+/// The shareable part of the URL (after the `#`), e.g. `12345678?v=2&t=both&l=5&p=1p&n=3&s=draw`
+/// or `12345678-87654321?v=2&...` with different seeds for the departments and the cards.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Link {
+    pub seed: Seed,
+    pub version: Version,
+    pub options: Options,
+}
+
+impl Link {
+    /// Parses a link, options which are missing or invalid are taken from `defaults`.
+    ///
+    /// Only the options which existed before links contained options are taken from `defaults`, all newer ones
+    /// default to the behaviour before they were introduced (as old links don't contain them).
+    ///
+    /// Links without a version were created before versions were introduced and thus are [`Version::V1`],
+    /// unknown versions (from a newer application) are replaced by [`Version::LATEST`].
+    /// For all other versions values which are impossible with the tiles are [clamped](Options::clamp).
+    ///
+    /// Returns `None` if there is no valid seed.
+    pub fn parse(link: &str, defaults: Options) -> Option<Self> {
+        let (seed, query) = link.split_once('?').unwrap_or((link, ""));
+        let seed = seed.parse::<Seed>().ok()?;
+
+        let mut seed = seed;
+        let mut version = Version::V1;
+        let mut options = Options {
+            players: defaults.players,
+            tiles: defaults.tiles,
+            limit: defaults.limit,
+            permanent: defaults.permanent,
+            ..Options::default()
+        };
+        for (key, value) in query.split('&').filter_map(|kv| kv.split_once('=')) {
+            match key {
+                "v" => version = value.parse().unwrap_or(Version::LATEST),
+                "t" => options.tiles = value.parse().unwrap_or(options.tiles),
+                "l" => options.limit = value.parse().unwrap_or(options.limit),
+                "p" => options.permanent = value.parse().unwrap_or(options.permanent),
+                "n" => options.players = value.parse().unwrap_or(options.players),
+                "s" => options.sampling = value.parse().unwrap_or(options.sampling),
+                "x" => options.excluded = value.parse().unwrap_or(options.excluded),
+                "r" => options.required = value.parse().unwrap_or(options.required),
+                "f" => options.rules.forbidden = value.parse().unwrap_or(options.rules.forbidden),
+                "q" => options.rules.required = value.parse().unwrap_or(options.rules.required),
+                "e" => options.expansion = value.parse().unwrap_or(options.expansion),
+                "k1" | "k2" | "k3" | "k4" => {
+                    let row = key[1..].parse::<usize>().unwrap() - 1;
+                    seed.locked.0[row] = value.parse().ok();
+                }
+                _ => {}
+            }
+        }
+        // Version 1 handles all values (with possibly empty rows), later ones can't
+        if version != Version::V1 {
+            options.clamp();
+        }
+
+        Some(Self {
+            seed,
+            version,
+            options,
+        })
+    }
+}
+
+impl Display for Link {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}?v={}&t={}&l={}&p={}&n={}&s={}",
+            self.seed,
+            self.version,
+            self.options.tiles,
+            self.options.limit,
+            self.options.permanent,
+            self.options.players,
+            self.options.sampling
+        )?;
+        if !self.options.excluded.is_empty() {
+            write!(f, "&x={}", self.options.excluded)?;
+        }
+        if !self.options.required.is_empty() {
+            write!(f, "&r={}", self.options.required)?;
+        }
+        if !self.options.rules.forbidden.is_empty() {
+            write!(f, "&f={}", self.options.rules.forbidden)?;
+        }
+        if !self.options.rules.required.is_empty() {
+            write!(f, "&q={}", self.options.rules.required)?;
+        }
+        if self.options.expansion != Expansion::Any {
+            write!(f, "&e={}", self.options.expansion)?;
+        }
+        for (row, lock) in self.seed.locked.0.iter().enumerate() {
+            if let Some(lock) = lock {
+                write!(f, "&k{}={}", row + 1, lock)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,10 +1,17 @@
-use crate::buildings::{Buildings, BuildingsPane};
-use crate::setup::{Cards, SetupPane};
+use crate::buildings::BuildingsPane;
+use crate::draw::DrawPane;
+use crate::setup::SetupPane;
 use gloo_history::{BrowserHistory, History, HistoryListener};
 use gloo_storage::errors::StorageError;
 use gloo_storage::{LocalStorage, Storage};
+use random_carnegie::code::SetupCode;
+use random_carnegie::departments::{Building, Infeasible, Locked};
+use random_carnegie::link::Link;
+use random_carnegie::options::{Options, Players};
+use random_carnegie::seed::Seed;
+use random_carnegie::version::Version;
+use random_carnegie::{count_layouts, generate, Setup};
 use serde::{Deserialize, Serialize};
-use serde_repr::{Deserialize_repr, Serialize_repr};
 use web_sys::{HtmlInputElement, KeyboardEvent};
 use yew::{html, Component, Context, Html, NodeRef};
 use yew_bootstrap::component::{Button, ButtonSize};
@@ -13,23 +20,17 @@ use yew_bootstrap::util::Color;
 use yewdux::mrc::Mrc;
 
 pub(crate) mod buildings;
+pub(crate) mod draw;
 pub(crate) mod setup;
 
-#[derive(Default)]
 pub(crate) struct Data {
-    players: Players,
-    buildings: Buildings,
-    cards: Cards,
-}
-
-#[derive(Copy, Clone, Default, Eq, PartialEq, Deserialize_repr, Serialize_repr)]
-#[repr(u8)]
-enum Players {
-    All = 0,
-    #[default]
-    Four = 4,
-    Three = 3,
-    Two = 2,
+    options: Options,
+    setup: Result<Setup, Infeasible>,
+    locked: Locked,
+    // The setup comes from a setup code and not from the seed
+    from_code: bool,
+    // The number of possible grids for `options`, if they are feasible
+    layouts: Option<u64>,
 }
 
 #[derive(Default, Deserialize, Serialize)]
@@ -42,7 +43,14 @@ impl State {
 }
 
 pub(crate) struct App {
-    seed: u64,
+    seed: Seed,
+    version: Version,
+    // Shown instead of the setup for the seed
+    code: Option<SetupCode>,
+    // The options `data.layouts` was counted for, counting them again may take a while
+    counted: Option<Options>,
+    // The options as saved in the local storage, the shown ones may come from a link
+    saved: Options,
     data: Mrc<Data>,
     edit_seed: bool,
     inp_seed: NodeRef,
@@ -55,20 +63,124 @@ pub(crate) struct App {
 
 impl App {
     fn rand(&mut self) {
-        self.seed = Into::<u64>::into(rand::random::<u32>()) % 100000000;
-        self.set_seed(self.seed);
+        self.code = None;
+        self.seed = Seed {
+            locked: self.seed.locked,
+            ..Seed::random()
+        };
+        self.version = Version::LATEST;
+        self.generate();
+    }
+    fn rand_departments(&mut self) {
+        self.code = None;
+        self.seed.departments = Seed::random_part();
+        self.version = Version::LATEST;
+        self.generate();
     }
-    fn set_seed(&mut self, seed: u64) {
+    fn rand_cards(&mut self) {
+        self.code = None;
+        self.seed.cards = Seed::random_part();
+        self.version = Version::LATEST;
+        self.generate();
+    }
+    fn generate(&mut self) {
         let mut data = self.data.borrow_mut();
-        data.buildings.rand(seed);
-        data.cards.rand(seed);
+        if let Some(code) = &self.code {
+            data.options.players = code.players;
+            data.setup = Ok(code.setup.clone());
+            data.locked = Locked::default();
+            data.from_code = true;
+        } else {
+            // Only the first version supports the values which are impossible with the tiles
+            if self.version != Version::V1 {
+                data.options.clamp();
+            }
+            data.setup = generate(self.seed, self.version, &data.options);
+            data.locked = self.seed.locked;
+            data.from_code = false;
+        }
+        if self.counted != Some(data.options) {
+            self.counted = Some(data.options);
+            data.layouts = count_layouts(&data.options).ok();
+        }
+    }
+    fn link(&self) -> Link {
+        Link {
+            seed: self.seed,
+            version: self.version,
+            options: self.data.borrow().options,
+        }
+    }
+    fn path(&self) -> String {
+        match &self.code {
+            Some(code) => format!("{}#code={}", self.base, code),
+            None => format!("{}#{}", self.base, self.link()),
+        }
+    }
+    fn setup_code(&self) -> Option<SetupCode> {
+        let data = self.data.borrow();
+        match &self.code {
+            Some(code) => Some(code.clone()),
+            None => data.setup.as_ref().ok().map(|setup| SetupCode {
+                players: data.options.players,
+                setup: setup.clone(),
+            }),
+        }
     }
+    /// The saved options as they are used by the version, see [`App::generate`].
+    fn saved_options(&self) -> Options {
+        let mut options = self.saved;
+        if self.version != Version::V1 {
+            options.clamp();
+        }
+        options
+    }
+    fn load_options() -> Options {
+        let state = LocalStorage::get::<State>(State::KEY).unwrap_or_default();
+        let mut options = Options {
+            players: state.players,
+            ..Default::default()
+        };
+        buildings::State::load(&mut options);
+        options
+    }
+    fn save_options(&mut self, options: Options) {
+        self.saved = options;
+        let _: Result<(), StorageError> = LocalStorage::set(
+            State::KEY,
+            State {
+                players: options.players,
+            },
+        );
+        buildings::State::save(&options);
+    }
+}
+
+/// Whether [`Version::V1`] shows the change of the options, it ignores most of them.
+fn v1_follows(old: &Options, new: &Options) -> bool {
+    (new.limit == old.limit || new.limit.same().is_some())
+        && (new.permanent == old.permanent || new.permanent.same().is_some())
+        && *new
+            == Options {
+                players: new.players,
+                tiles: new.tiles,
+                limit: new.limit,
+                permanent: new.permanent,
+                ..*old
+            }
 }
 
 enum AppMsg {
     HistoryChanged,
     Rand,
-    Players(Players),
+    RandDepartments,
+    RandCards,
+    Lock(usize),
+    Replace(usize, Building, Building),
+    LatestVersion,
+    Options(Options),
+    SaveOptions,
+    RestoreOptions,
     EditSeed,
     SetSeed,
     CancelSeed,
@@ -86,13 +198,20 @@ impl Component for App {
         let history_listener =
             browser_history.listen(move || link_cloned.send_message(AppMsg::HistoryChanged));
 
-        let state = LocalStorage::get::<State>(State::KEY).unwrap_or_default();
+        let options = App::load_options();
 
         let mut app = App {
-            seed: 0,
+            seed: Seed::new(0),
+            version: Version::LATEST,
+            code: None,
+            counted: None,
+            saved: options,
             data: Mrc::new(Data {
-                players: state.players,
-                ..Default::default()
+                options,
+                setup: Ok(Setup::default()),
+                locked: Locked::default(),
+                from_code: false,
+                layouts: None,
             }),
             edit_seed: false,
             inp_seed: NodeRef::default(),
@@ -109,15 +228,31 @@ impl Component for App {
         match msg {
             AppMsg::HistoryChanged => {
                 let loc = self.browser_history.location();
-                self.seed = loc
-                    .hash()
-                    .trim_start_matches('#')
-                    .parse()
-                    .unwrap_or_else(|_| Into::<u64>::into(rand::random::<u32>()))
-                    % 100000000;
-                self.set_seed(self.seed);
+                let hash = loc.hash();
+                let hash = hash.trim_start_matches('#');
+                self.code = hash
+                    .strip_prefix("code=")
+                    .and_then(|code| code.parse::<SetupCode>().ok());
+                if self.code.is_some() {
+                    self.version = Version::LATEST;
+                } else {
+                    // Options missing in the link are the saved ones, not the ones of a previous link
+                    let options = self.saved;
+                    let link = Link::parse(hash, options).unwrap_or_else(|| Link {
+                        seed: Seed::random(),
+                        version: Version::LATEST,
+                        options,
+                    });
+                    self.seed = link.seed;
+                    self.version = link.version;
+                    self.data.borrow_mut().options = link.options;
+                }
+                self.generate();
 
-                let new_path = format!("{}/#{:08}", self.base, self.seed);
+                let new_path = match &self.code {
+                    Some(code) => format!("{}/#code={}", self.base, code),
+                    None => format!("{}/#{}", self.base, self.link()),
+                };
 
                 if format!("{}{}", loc.path(), loc.hash()) == new_path || self.redirect_counter > 3
                 {
@@ -130,22 +265,106 @@ impl Component for App {
             }
             AppMsg::Rand => {
                 self.rand();
-                let new_path = format!("{}#{:08}", self.base, self.seed);
+                let new_path = self.path();
+                self.browser_history.push(new_path);
+                self.redirect_counter = 0;
+                false
+            }
+            AppMsg::RandDepartments => {
+                self.rand_departments();
+                let new_path = self.path();
+                self.browser_history.push(new_path);
+                self.redirect_counter = 0;
+                false
+            }
+            AppMsg::RandCards => {
+                self.rand_cards();
+                let new_path = self.path();
                 self.browser_history.push(new_path);
                 self.redirect_counter = 0;
                 false
             }
-            AppMsg::Players(players) => {
-                let mut data = self.data.borrow_mut();
-                if data.players != players {
-                    data.players = players;
-                    let _: Result<(), StorageError> =
-                        LocalStorage::set(State::KEY, State { players });
+            AppMsg::Lock(row) => {
+                if self.seed.locked.0[row].is_some() {
+                    self.seed.locked.0[row] = None;
+                } else if let Ok(setup) = &self.data.borrow().setup {
+                    self.seed.locked.0[row] = setup.lock(row);
+                    // Older versions don't support locked rows
+                    self.version = Version::LATEST;
+                }
+                self.generate();
+                let new_path = self.path();
+                self.browser_history.replace(new_path);
+                self.redirect_counter = 0;
+                true
+            }
+            AppMsg::Replace(row, from, to) => {
+                if let Some(code) = &mut self.code {
+                    code.replace(from, to);
+                } else if let Ok(setup) = &self.data.borrow().setup {
+                    // Older versions don't support locked rows, but with all rows locked the latest
+                    // version shows the same departments (and the cards never changed)
+                    if self.version < Version::LATEST {
+                        for (r, lock) in self.seed.locked.0.iter_mut().enumerate() {
+                            *lock = lock.or_else(|| setup.lock(r));
+                        }
+                        self.version = Version::LATEST;
+                    }
+                    if let Some(mut lock) = self.seed.locked.0[row].or_else(|| setup.lock(row)) {
+                        lock.replace(from, to);
+                        self.seed.locked.0[row] = Some(lock);
+                    }
+                }
+                self.generate();
+                let new_path = self.path();
+                self.browser_history.replace(new_path);
+                self.redirect_counter = 0;
+                true
+            }
+            AppMsg::LatestVersion => {
+                self.version = Version::LATEST;
+                self.generate();
+                let new_path = self.path();
+                self.browser_history.push(new_path);
+                self.redirect_counter = 0;
+                true
+            }
+            AppMsg::Options(options) => {
+                let old = self.data.borrow().options;
+                if old != options {
+                    // The setup of a code can't follow the new options
+                    self.code = None;
+                    // Changes are only saved if the link did not bring its own options
+                    if old == self.saved_options() {
+                        self.save_options(options);
+                    }
+                    if self.version == Version::V1 && !v1_follows(&old, &options) {
+                        self.version = Version::LATEST;
+                    }
+                    self.data.borrow_mut().options = options;
+                    self.generate();
+                    let new_path = self.path();
+                    self.browser_history.replace(new_path);
+                    self.redirect_counter = 0;
                     true
                 } else {
                     false
                 }
             }
+            AppMsg::SaveOptions => {
+                let options = self.data.borrow().options;
+                self.save_options(options);
+                true
+            }
+            AppMsg::RestoreOptions => {
+                self.code = None;
+                self.data.borrow_mut().options = self.saved;
+                self.generate();
+                let new_path = self.path();
+                self.browser_history.replace(new_path);
+                self.redirect_counter = 0;
+                true
+            }
             AppMsg::EditSeed => {
                 let inp_seed = self.inp_seed.cast::<HtmlInputElement>().unwrap();
                 inp_seed.set_class_name("seed_input");
@@ -160,9 +379,15 @@ impl Component for App {
                 let _ = inp_seed.blur();
                 inp_seed.set_class_name("seed_input_hidden");
 
-                if let Ok(new_seed) = inp_seed.value().parse() {
-                    self.seed = new_seed;
-                    let new_path = format!("{}#{:08}", self.base, self.seed);
+                if let Ok(new_seed) = inp_seed.value().parse::<Seed>() {
+                    self.code = None;
+                    // Like a random seed, a typed one keeps the locked rows
+                    self.seed = Seed {
+                        locked: self.seed.locked,
+                        ..new_seed
+                    };
+                    self.version = Version::LATEST;
+                    let new_path = self.path();
                     self.browser_history.push(new_path);
                     self.redirect_counter = 0;
                 }
@@ -188,7 +413,7 @@ impl Component for App {
             }
         }
 
-        let data = self.data.borrow();
+        let options = self.data.borrow().options;
         html! {
             <div class="app-wrap">
                 <nav class="navbar sticky-top bg-body-tertiary">
@@ -201,15 +426,16 @@ impl Component for App {
                 <main class="container py-4" style="text-align: center">
                     <div class="d-grid gap-3">
                         <div>
-                            {"Seed: "}
+                            if self.code.is_none() {
+                                {"Seed: "}
+                            }
                             <input
                                 class="seed_input_hidden"
                                 type="text"
-                                value={format!("{:08}",self.seed)}
-                                maxlength=8
-                                size=12
-                                inputmode="numeric"
-                                pattern="\\d*"
+                                value={self.seed.to_string()}
+                                maxlength=17
+                                size=20
+                                pattern="\\d*(-\\d*)?"
                                 onkeyup={ctx.link().callback(key_to_message)}
                                 ref={&self.inp_seed}
                             />
@@ -223,13 +449,65 @@ impl Component for App {
                                     {BI::X}
                                 </Button>
                             }else{
-                                <a onclick={ctx.link().callback(|_| AppMsg::EditSeed)}>{format!("{:08}",self.seed)}</a>
-                                {" "}
+                                if self.code.is_none() {
+                                    <a onclick={ctx.link().callback(|_| AppMsg::EditSeed)}>{self.seed.to_string()}</a>
+                                    {" "}
+                                }
+                                if let Some(code) = self.setup_code() {
+                                    <span class="text-body-secondary" title="Fingerprint: devices showing the same words show the same setup">
+                                        {BI::FINGERPRINT}{" "}{code.fingerprint()}
+                                    </span>
+                                    {" "}
+                                }
                                 <Button size={ButtonSize::Small} onclick={ctx.link().callback(|_| AppMsg::Rand)}>
                                     {BI::ARROW_CLOCKWISE}
                                 </Button>
+                                {" "}
+                                <Button style={Color::Secondary} size={ButtonSize::Small} onclick={ctx.link().callback(|_| AppMsg::RandDepartments)}>
+                                    {BI::ARROW_CLOCKWISE}{" Departments"}
+                                </Button>
+                                // All shows the blocked spaces for 3 and 2 players
+                                if options.players.disks() > 0 || options.players == Players::All {
+                                    {" "}
+                                    <Button style={Color::Secondary} size={ButtonSize::Small} onclick={ctx.link().callback(|_| AppMsg::RandCards)}>
+                                        {BI::ARROW_CLOCKWISE}{" Donations/cities"}
+                                    </Button>
+                                }
                             }
                         </div>
+                        if let Some(code) = self.setup_code() {
+                            <div>
+                                <small>
+                                    {"Setup code: "}
+                                    <a href={format!("#code={code}")}>{code.to_string()}</a>
+                                </small>
+                            </div>
+                        }
+                        if self.code.is_some() {
+                            <div class="alert alert-info mb-0" role="alert">
+                                {"This setup was loaded from a setup code, it stays the same regardless of the settings and versions of the randomizer."}
+                            </div>
+                        }
+                        if self.version < Version::LATEST {
+                            <div class="alert alert-warning mb-0" role="alert">
+                                {format!("This link was created by an older version of the randomizer (v{}), it still shows the same setup as back then. ", self.version)}
+                                <Button size={ButtonSize::Small} onclick={ctx.link().callback(|_| AppMsg::LatestVersion)}>
+                                    {"Use the latest version"}
+                                </Button>
+                            </div>
+                        }
+                        if options != self.saved_options() {
+                            <div class="alert alert-info mb-0" role="alert">
+                                {"This link uses other settings than your saved ones. "}
+                                <Button size={ButtonSize::Small} onclick={ctx.link().callback(|_| AppMsg::SaveOptions)}>
+                                    {"Save as my settings"}
+                                </Button>
+                                {" "}
+                                <Button style={Color::Secondary} size={ButtonSize::Small} onclick={ctx.link().callback(|_| AppMsg::RestoreOptions)}>
+                                    {"Use my settings"}
+                                </Button>
+                            </div>
+                        }
                         <div>
                             {"Show for: "}
                             <div class="btn-group" role="group">
@@ -239,8 +517,8 @@ impl Component for App {
                                     name="players"
                                     id="players0"
                                     autocomplete="off"
-                                    checked={data.players == Players::All}
-                                    onchange={ctx.link().callback(|_| AppMsg::Players(Players::All))}
+                                    checked={options.players == Players::All}
+                                    onchange={ctx.link().callback(move |_| AppMsg::Options(Options { players: Players::All, ..options }))}
                                 />
                                 <label class="btn btn-outline-primary" for="players0">{"All"}</label>
 
@@ -250,8 +528,8 @@ impl Component for App {
                                     name="players"
                                     id="players1"
                                     autocomplete="off"
-                                    checked={data.players == Players::Four}
-                                    onchange={ctx.link().callback(|_| AppMsg::Players(Players::Four))}
+                                    checked={options.players == Players::Four}
+                                    onchange={ctx.link().callback(move |_| AppMsg::Options(Options { players: Players::Four, ..options }))}
                                 />
                                 <label class="btn btn-outline-primary" for="players1">{"4p"}</label>
 
@@ -261,8 +539,8 @@ impl Component for App {
                                     name="players"
                                     id="players2"
                                     autocomplete="off"
-                                    checked={data.players == Players::Three}
-                                    onchange={ctx.link().callback(|_| AppMsg::Players(Players::Three))}
+                                    checked={options.players == Players::Three}
+                                    onchange={ctx.link().callback(move |_| AppMsg::Options(Options { players: Players::Three, ..options }))}
                                 />
                                 <label class="btn btn-outline-primary" for="players2">{"3p"}</label>
 
@@ -272,13 +550,14 @@ impl Component for App {
                                     name="players"
                                     id="players3"
                                     autocomplete="off"
-                                    checked={data.players == Players::Two}
-                                    onchange={ctx.link().callback(|_| AppMsg::Players(Players::Two))}
+                                    checked={options.players == Players::Two}
+                                    onchange={ctx.link().callback(move |_| AppMsg::Options(Options { players: Players::Two, ..options }))}
                                 />
                                 <label class="btn btn-outline-primary" for="players3">{"2p"}</label>
                             </div>
                         </div>
-                        <BuildingsPane data={self.data.clone()} />
+                        <BuildingsPane data={self.data.clone()} on_change={ctx.link().callback(AppMsg::Options)} on_lock={ctx.link().callback(AppMsg::Lock)} on_replace={ctx.link().callback(|(row, from, to)| AppMsg::Replace(row, from, to))} />
+                        <DrawPane data={self.data.clone()} />
                         <SetupPane data={self.data.clone()} />
                     </div>
                 </main>
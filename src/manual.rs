@@ -0,0 +1,113 @@
+//! Places department tiles which were drawn by hand, like the draw sampling of the generator.
+
+use crate::departments::{Building, Department, Departments, Infeasible, Locked};
+use crate::options::{Options, Players};
+use crate::solver::{self, Grid};
+use std::fmt::{Display, Formatter};
+
+/// Why a drawn tile is discarded.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Discard {
+    /// All 32 tiles are already placed.
+    Complete,
+    /// The tile is not part of the selected tiles (base game and/or expansion).
+    NotInTiles,
+    /// The tile is excluded.
+    Excluded,
+    /// Both copies of the tile are already placed.
+    BothCopies,
+    /// Its row already has all 8 tiles.
+    RowFull,
+    /// No valid grid has (another copy of) the tile in its row.
+    NotPossible,
+}
+
+impl Display for Discard {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Discard::Complete => write!(f, "the grid is already complete"),
+            Discard::NotInTiles => write!(f, "not one of the selected tiles"),
+            Discard::Excluded => write!(f, "excluded"),
+            Discard::BothCopies => write!(f, "both copies are already placed"),
+            Discard::RowFull => write!(f, "its row is already full"),
+            Discard::NotPossible => write!(
+                f,
+                "its row can't be completed with it (different or permanent departments, expansion, required departments or rules)"
+            ),
+        }
+    }
+}
+
+/// The grid while the tiles are placed.
+#[derive(Clone)]
+pub struct ManualDraw {
+    options: Options,
+    grid: Grid,
+    departments: Departments,
+    used: [u8; 33],
+    count: usize,
+}
+
+impl ManualDraw {
+    pub fn new(options: &Options) -> Result<Self, Infeasible> {
+        Ok(Self {
+            options: *options,
+            grid: solver::grid(options, &Locked::default())?,
+            departments: Departments::default(),
+            used: [0; 33],
+            count: 0,
+        })
+    }
+
+    /// Places the next drawn tile if all rows can still be completed with it.
+    pub fn place(&mut self, b: Building) -> Result<(), Discard> {
+        if self.is_complete() {
+            return Err(Discard::Complete);
+        }
+        let row = b.row();
+        let Some(i) = self.grid.rows[row].index(b) else {
+            return Err(if !b.is_tile(self.options.tiles) {
+                Discard::NotInTiles
+            } else {
+                Discard::Excluded
+            });
+        };
+        let n = self.used[b.value() as usize];
+        if n >= 2 {
+            return Err(Discard::BothCopies);
+        }
+        if self.departments.0[row]
+            .values()
+            .map(Vec::len)
+            .sum::<usize>()
+            == 8
+        {
+            return Err(Discard::RowFull);
+        }
+        if !self.grid.rows[row]
+            .layouts
+            .iter()
+            .any(|l| l[i] > n && self.grid.possible(row, l))
+        {
+            return Err(Discard::NotPossible);
+        }
+        self.used[b.value() as usize] += 1;
+        self.grid.retain(row, |l| l[i] > n);
+        self.departments.0[row]
+            .entry(b)
+            .or_default()
+            .push(self.count);
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Whether all 32 tiles are placed.
+    pub fn is_complete(&self) -> bool {
+        self.count == 32
+    }
+
+    /// The placed tiles, the copies for the player counts are given by the order they were placed.
+    pub fn rows(&self, players: Players) -> [Vec<Department>; 4] {
+        self.departments.rows(players)
+    }
+}
@@ -0,0 +1,465 @@
+use crate::departments::{BuildingSet, Pairs};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+use std::fmt::{Display, Formatter};
+use std::ops::{Index, IndexMut};
+use std::str::FromStr;
+
+#[derive(Copy, Clone, Default, Eq, PartialEq, Debug, Deserialize_repr, Serialize_repr)]
+#[repr(u8)]
+pub enum Players {
+    All = 0,
+    #[default]
+    Four = 4,
+    Three = 3,
+    Two = 2,
+}
+
+impl Players {
+    /// Number of disks used to block donations and cities.
+    pub fn disks(self) -> usize {
+        match self {
+            Players::All => 0,
+            Players::Four => 0,
+            Players::Three => 9,
+            Players::Two => 18,
+        }
+    }
+
+    /// A copy placed at slot `s` is used if `s & mask != mask`.
+    pub(crate) fn layer_mask(self) -> usize {
+        match self {
+            Players::All => 32,
+            Players::Four => 7,
+            Players::Three => 3,
+            Players::Two => 1,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Default, Debug, Deserialize, Serialize)]
+pub enum Tiles {
+    #[default]
+    Base,
+    Both,
+    Expansion,
+}
+
+impl Tiles {
+    /// The values of [`LimitTypes`] which make a difference with these tiles.
+    ///
+    /// With only one edition every row has just 4 departments, which are all used.
+    pub fn limits(self) -> &'static [LimitTypes] {
+        match self {
+            Tiles::Both => &[
+                LimitTypes::Four,
+                LimitTypes::Five,
+                LimitTypes::Six,
+                LimitTypes::All,
+            ],
+            Tiles::Base | Tiles::Expansion => &[LimitTypes::Four],
+        }
+    }
+
+    /// The values of [`Permanent`] which can be fulfilled with these tiles.
+    ///
+    /// With only one edition every row has exactly one permanent department, which is always used.
+    pub fn permanents(self) -> &'static [Permanent] {
+        match self {
+            Tiles::Both => &[
+                Permanent::Zero,
+                Permanent::ZeroPlus,
+                Permanent::One,
+                Permanent::OnePlus,
+                Permanent::Two,
+            ],
+            Tiles::Base | Tiles::Expansion => &[Permanent::One],
+        }
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Default, Debug, Deserialize_repr, Serialize_repr)]
+#[repr(u8)]
+pub enum LimitTypes {
+    #[default]
+    Four = 4,
+    Five = 5,
+    Six = 6,
+    All = 8,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Default, Debug, Deserialize, Serialize)]
+pub enum Permanent {
+    Zero,
+    ZeroPlus,
+    #[default]
+    One,
+    OnePlus,
+    Two,
+}
+
+impl Permanent {
+    pub fn min(&self) -> usize {
+        match self {
+            Permanent::Zero => 0,
+            Permanent::ZeroPlus => 0,
+            Permanent::One => 1,
+            Permanent::OnePlus => 1,
+            Permanent::Two => 2,
+        }
+    }
+
+    pub fn max(&self) -> usize {
+        match self {
+            Permanent::Zero => 0,
+            Permanent::ZeroPlus => 2,
+            Permanent::One => 1,
+            Permanent::OnePlus => 2,
+            Permanent::Two => 2,
+        }
+    }
+}
+
+impl Display for Players {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Players::All => write!(f, "all"),
+            players => write!(f, "{}", *players as u8),
+        }
+    }
+}
+
+impl FromStr for Players {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "all" => Ok(Players::All),
+            "4" => Ok(Players::Four),
+            "3" => Ok(Players::Three),
+            "2" => Ok(Players::Two),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for Tiles {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Tiles::Base => "base",
+            Tiles::Both => "both",
+            Tiles::Expansion => "expansion",
+        })
+    }
+}
+
+impl FromStr for Tiles {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "base" => Ok(Tiles::Base),
+            "both" => Ok(Tiles::Both),
+            "expansion" => Ok(Tiles::Expansion),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for LimitTypes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", *self as u8)
+    }
+}
+
+impl FromStr for LimitTypes {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "4" => Ok(LimitTypes::Four),
+            "5" => Ok(LimitTypes::Five),
+            "6" => Ok(LimitTypes::Six),
+            "8" => Ok(LimitTypes::All),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Uses `p` instead of `+` (i.e. `0p` and `1p`) to be usable in URLs.
+impl Display for Permanent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Permanent::Zero => "0",
+            Permanent::ZeroPlus => "0p",
+            Permanent::One => "1",
+            Permanent::OnePlus => "1p",
+            Permanent::Two => "2",
+        })
+    }
+}
+
+impl FromStr for Permanent {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "0" => Ok(Permanent::Zero),
+            "0p" | "0+" => Ok(Permanent::ZeroPlus),
+            "1" => Ok(Permanent::One),
+            "1p" | "1+" => Ok(Permanent::OnePlus),
+            "2" => Ok(Permanent::Two),
+            _ => Err(()),
+        }
+    }
+}
+
+/// How many different expansion departments are in each row, only used with [`Tiles::Both`].
+#[derive(Copy, Clone, Eq, PartialEq, Default, Debug, Deserialize, Serialize)]
+pub enum Expansion {
+    #[default]
+    Any,
+    OnePlus,
+    One,
+    Two,
+    Three,
+    Four,
+}
+
+impl Expansion {
+    pub fn min(&self) -> usize {
+        match self {
+            Expansion::Any => 0,
+            Expansion::OnePlus => 1,
+            Expansion::One => 1,
+            Expansion::Two => 2,
+            Expansion::Three => 3,
+            Expansion::Four => 4,
+        }
+    }
+
+    pub fn max(&self) -> usize {
+        match self {
+            Expansion::Any => 8,
+            Expansion::OnePlus => 8,
+            Expansion::One => 1,
+            Expansion::Two => 2,
+            Expansion::Three => 3,
+            Expansion::Four => 4,
+        }
+    }
+}
+
+/// Uses `p` instead of `+` (i.e. `1p`) to be usable in URLs.
+impl Display for Expansion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Expansion::Any => "any",
+            Expansion::OnePlus => "1p",
+            Expansion::One => "1",
+            Expansion::Two => "2",
+            Expansion::Three => "3",
+            Expansion::Four => "4",
+        })
+    }
+}
+
+impl FromStr for Expansion {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "any" => Ok(Expansion::Any),
+            "1p" | "1+" => Ok(Expansion::OnePlus),
+            "1" => Ok(Expansion::One),
+            "2" => Ok(Expansion::Two),
+            "3" => Ok(Expansion::Three),
+            "4" => Ok(Expansion::Four),
+            _ => Err(()),
+        }
+    }
+}
+
+/// How the departments are chosen among all valid layouts.
+#[derive(Copy, Clone, Eq, PartialEq, Default, Debug, Deserialize, Serialize)]
+pub enum Sampling {
+    /// Draw tiles like from the box, some layouts are more likely than others.
+    #[default]
+    Draw,
+    /// Every valid layout is equally likely.
+    Uniform,
+}
+
+impl Display for Sampling {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Sampling::Draw => "draw",
+            Sampling::Uniform => "uniform",
+        })
+    }
+}
+
+impl FromStr for Sampling {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "draw" => Ok(Sampling::Draw),
+            "uniform" => Ok(Sampling::Uniform),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A setting for each of the four rows.
+#[derive(Copy, Clone, Eq, PartialEq, Default, Debug, Serialize)]
+#[serde(transparent)]
+pub struct PerRow<T>(pub [T; 4]);
+
+impl<T: Copy + PartialEq> PerRow<T> {
+    pub fn all(value: T) -> Self {
+        Self([value; 4])
+    }
+
+    /// The value if it is the same for all rows.
+    pub fn same(&self) -> Option<T> {
+        self.0[1..]
+            .iter()
+            .all(|v| *v == self.0[0])
+            .then_some(self.0[0])
+    }
+}
+
+impl<T> Index<usize> for PerRow<T> {
+    type Output = T;
+
+    fn index(&self, row: usize) -> &Self::Output {
+        &self.0[row]
+    }
+}
+
+impl<T> IndexMut<usize> for PerRow<T> {
+    fn index_mut(&mut self, row: usize) -> &mut Self::Output {
+        &mut self.0[row]
+    }
+}
+
+/// Also accepts a single value for all rows, which is how it was saved before.
+impl<'de, T: Deserialize<'de> + Copy> Deserialize<'de> for PerRow<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Saved<T> {
+            All(T),
+            Rows([T; 4]),
+        }
+
+        Ok(match Saved::deserialize(deserializer)? {
+            Saved::All(value) => Self([value; 4]),
+            Saved::Rows(rows) => Self(rows),
+        })
+    }
+}
+
+/// A single value if it is the same for all rows, otherwise the four values comma-separated.
+impl<T: Display + Copy + PartialEq> Display for PerRow<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(value) = self.same() {
+            write!(f, "{}", value)
+        } else {
+            write!(f, "{},{},{},{}", self.0[0], self.0[1], self.0[2], self.0[3])
+        }
+    }
+}
+
+impl<T: FromStr + Copy> FromStr for PerRow<T> {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let values = s
+            .split(',')
+            .map(|v| v.parse().map_err(|_| ()))
+            .collect::<Result<Vec<T>, ()>>()?;
+        match values[..] {
+            [value] => Ok(Self([value; 4])),
+            [a, b, c, d] => Ok(Self([a, b, c, d])),
+            _ => Err(()),
+        }
+    }
+}
+
+/// All settings which influence the generated setup.
+#[derive(Copy, Clone, Eq, PartialEq, Default, Debug, Deserialize, Serialize)]
+pub struct Options {
+    pub players: Players,
+    pub tiles: Tiles,
+    /// [`Version::V1`](crate::version::Version::V1) only supports the same value for all rows.
+    pub limit: PerRow<LimitTypes>,
+    /// [`Version::V1`](crate::version::Version::V1) only supports the same value for all rows.
+    pub permanent: PerRow<Permanent>,
+    /// Not supported by [`Version::V1`](crate::version::Version::V1).
+    pub sampling: Sampling,
+    /// Departments which are never used, not supported by [`Version::V1`](crate::version::Version::V1).
+    pub excluded: BuildingSet,
+    /// Departments which are always used with any player count, both copies if possible, not
+    /// supported by [`Version::V1`](crate::version::Version::V1).
+    pub required: BuildingSet,
+    /// Not supported by [`Version::V1`](crate::version::Version::V1).
+    pub rules: Rules,
+    /// Not supported by [`Version::V1`](crate::version::Version::V1).
+    pub expansion: Expansion,
+}
+
+impl Options {
+    /// Replaces all limits and permanent departments which aren't supported by the tiles with the
+    /// first supported value.
+    pub fn clamp(&mut self) {
+        for row in 0..4 {
+            if !self.tiles.limits().contains(&self.limit[row]) {
+                self.limit[row] = self.tiles.limits()[0];
+            }
+            if !self.tiles.permanents().contains(&self.permanent[row]) {
+                self.permanent[row] = self.tiles.permanents()[0];
+            }
+        }
+    }
+}
+
+/// Pairs of departments which may not or must be used together.
+///
+/// A department counts as used if it is in the full grid.
+#[derive(Copy, Clone, Default, Eq, PartialEq, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Rules {
+    /// Never both departments of a pair.
+    pub forbidden: Pairs,
+    /// The second department of a pair whenever the first one is used.
+    pub required: Pairs,
+}
+
+impl Rules {
+    pub fn is_empty(&self) -> bool {
+        self.forbidden.is_empty() && self.required.is_empty()
+    }
+
+    /// All departments which are part of a rule.
+    pub fn tiles(&self) -> BuildingSet {
+        let mut tiles = BuildingSet::default();
+        for (first, second) in self.forbidden.iter().chain(self.required.iter()) {
+            tiles.insert(first);
+            tiles.insert(second);
+        }
+        tiles
+    }
+
+    /// Whether using exactly the departments of `used` which are part of a rule is allowed.
+    pub fn allows(&self, used: BuildingSet) -> bool {
+        self.forbidden
+            .iter()
+            .all(|(first, second)| !used.contains(first) || !used.contains(second))
+            && self
+                .required
+                .iter()
+                .all(|(first, second)| !used.contains(first) || used.contains(second))
+    }
+}
@@ -0,0 +1,75 @@
+use crate::departments::Locked;
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// The seed of a setup, with one sub-seed per component so that each can be re-rolled alone.
+///
+/// Seeds are shown with 8 digits, larger values are reduced to that.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize)]
+pub struct Seed {
+    /// Used for the department grid.
+    pub departments: u64,
+    /// Used for the blocked donations and cities.
+    pub cards: u64,
+    /// Department rows which are kept regardless of `departments`.
+    #[serde(skip)]
+    pub locked: Locked,
+}
+
+impl Seed {
+    const MODULUS: u64 = 100000000;
+
+    /// Uses the same sub-seed for all components, like all seeds before they were split.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            departments: seed % Self::MODULUS,
+            cards: seed % Self::MODULUS,
+            locked: Locked::default(),
+        }
+    }
+
+    /// A random 8 digit sub-seed.
+    pub fn random_part() -> u64 {
+        Into::<u64>::into(rand::random::<u32>()) % Self::MODULUS
+    }
+
+    pub fn random() -> Self {
+        Self::new(Self::random_part())
+    }
+}
+
+impl From<u64> for Seed {
+    fn from(seed: u64) -> Self {
+        Self::new(seed)
+    }
+}
+
+/// A single number if both sub-seeds are equal, otherwise `departments-cards`.
+///
+/// The locked rows are not included.
+impl Display for Seed {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.departments == self.cards {
+            write!(f, "{:08}", self.departments)
+        } else {
+            write!(f, "{:08}-{:08}", self.departments, self.cards)
+        }
+    }
+}
+
+impl FromStr for Seed {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let part = |s: &str| s.parse::<u64>().map(|v| v % Self::MODULUS).map_err(|_| ());
+        match s.split_once('-') {
+            None => part(s).map(Self::new),
+            Some((departments, cards)) => Ok(Self {
+                departments: part(departments)?,
+                cards: part(cards)?,
+                locked: Locked::default(),
+            }),
+        }
+    }
+}
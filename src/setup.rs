@@ -1,274 +1,235 @@
-use crate::{Data, Players};
-use rand::seq::SliceRandom;
-use rand::SeedableRng;
-use rand_pcg::Pcg64Mcg;
+use crate::Data;
+use random_carnegie::cards::{Blocked, Cards, City, Drawn, CARDS};
+use random_carnegie::options::Players;
 use std::collections::BTreeMap;
-use yew::virtual_dom::{VNode, VText};
-use yew::{function_component, html, AttrValue, Html, Properties, ToHtml};
+use yew::{function_component, html, use_state, Callback, Html, Properties};
+use yew_bootstrap::component::{Button, ButtonSize};
 use yew_bootstrap::icons::BI;
+use yew_bootstrap::util::Color;
 use yewdux::mrc::Mrc;
 
-pub(crate) struct Cards(Vec<u8>);
-
-impl Default for Cards {
-    fn default() -> Self {
-        Self((0..20).collect())
-    }
-}
-
-impl Cards {
-    pub(crate) fn rand(&mut self, seed: u64) {
-        self.0.sort();
-        let mut rng = Pcg64Mcg::seed_from_u64(seed ^ 0x4362256e);
-        self.0.shuffle(&mut rng);
-    }
+#[derive(Properties, PartialEq)]
+pub(crate) struct Props {
+    pub data: Mrc<Data>,
 }
 
-#[derive(Eq, PartialEq, Ord, PartialOrd, Copy, Clone)]
-pub enum City {
-    // WEST
-    Boise,
-    Denver,     //3
-    LosAngeles, //3
-    Portland,
-    Reno,
-    SaltLakeCity,
-    SanFrancisco,
-    SantaFe,
-    // MIDWEST
-    Chicago,
-    Cincinnati,
-    Duluth,
-    Fargo,
-    KansasCity, //3
-    Omaha,
-    StLouis, //3
-    StPaul,
-    // EAST
-    Albany, //3
-    Boston, //3
-    NewYork,
-    Pittsburgh, //3
-    Washington, //3
-    // SOUTH
-    Atlanta, //3
-    Charleston,
-    Dallas,
-    Houston, //3
-    Memphis,
-    NewOrleans,
-    SanAntonio,
-}
+fn blocked(donations: &[[bool; 4]; 5], cities: &BTreeMap<City, usize>) -> Html {
+    let hd = donations.iter().map(|row| {
+        let row = row
+            .iter()
+            .map(|e| html! {<td>{if *e {{BI::CIRCLE_FILL}} else {BI::CIRCLE}}</td>});
+        html! {<tr>{for row}</tr>}
+    });
+    let hc = cities.iter().map(|(&ci, &co)| {
+        if co == 1 {
+            html! {<><span class={"hidden"}>{"2 × "}</span><span class={ci.region()}>{ci.name()}</span><span class={"hidden"}>{" × 0"}</span><br/></>}
+        } else {
+            html! {<>{co}{" × "}<span class={ci.region()}>{ci.name()}</span><span class={"hidden"}>{" × 0"}</span><br/></>}
+        }
+    });
 
-impl ToHtml for City {
-    fn to_html(&self) -> Html {
-        VNode::VText(VText {
-            text: AttrValue::Static(match self {
-                City::Boise => "Boise",
-                City::Denver => "Denver",
-                City::LosAngeles => "Los Angeles",
-                City::Portland => "Portland",
-                City::Reno => "Reno",
-                City::SaltLakeCity => "Salt Lake City",
-                City::SanFrancisco => "San Francisco",
-                City::SantaFe => "Santa Fe",
-                City::Chicago => "Chicago",
-                City::Cincinnati => "Cincinnati",
-                City::Duluth => "Duluth",
-                City::Fargo => "Fargo",
-                City::KansasCity => "Kansas City",
-                City::Omaha => "Omaha",
-                City::StLouis => "St Louis",
-                City::StPaul => "St Paul",
-                City::Albany => "Albany",
-                City::Boston => "Boston",
-                City::NewYork => "New York",
-                City::Pittsburgh => "Pittsburgh",
-                City::Washington => "Washington",
-                City::Atlanta => "Atlanta",
-                City::Charleston => "Charleston",
-                City::Dallas => "Dallas",
-                City::Houston => "Houston",
-                City::Memphis => "Memphis",
-                City::NewOrleans => "New Orleans",
-                City::SanAntonio => "San Antonio",
-            }),
-        })
+    html! {
+        <>
+        <table align="center"><thead><th colspan="4">{"Blocked donations"}</th></thead><tbody>{for hd}</tbody></table>
+        <table align="center"><thead><th colspan="4">{"Blocked cities"}</th></thead><tbody>{for hc}</tbody></table>
+        </>
     }
 }
 
-impl City {
-    fn spaces(self) -> usize {
-        match self {
-            City::NewYork | City::Chicago | City::NewOrleans | City::SanFrancisco => 5,
-
-            City::Albany
-            | City::Boston
-            | City::Pittsburgh
-            | City::Washington
-            | City::KansasCity
-            | City::StLouis
-            | City::Atlanta
-            | City::Houston
-            | City::Denver
-            | City::LosAngeles => 3,
-
-            City::Cincinnati
-            | City::Duluth
-            | City::Fargo
-            | City::Omaha
-            | City::StPaul
-            | City::Charleston
-            | City::Dallas
-            | City::Memphis
-            | City::SanAntonio
-            | City::Boise
-            | City::Portland
-            | City::Reno
-            | City::SaltLakeCity
-            | City::SantaFe => 1,
-        }
-    }
-
-    fn class(self) -> &'static str {
-        match self {
-            City::Boise
-            | City::Denver
-            | City::LosAngeles
-            | City::Portland
-            | City::Reno
-            | City::SaltLakeCity
-            | City::SanFrancisco
-            | City::SantaFe => "west",
-            City::Chicago
-            | City::Cincinnati
-            | City::Duluth
-            | City::Fargo
-            | City::KansasCity
-            | City::Omaha
-            | City::StLouis
-            | City::StPaul => "midwest",
-            City::Albany | City::Boston | City::NewYork | City::Pittsburgh | City::Washington => {
-                "east"
+fn drawn(drawn: &[Drawn]) -> Html {
+    let hs = drawn.iter().enumerate().map(|(i, card)| {
+        let (y, x) = card.space();
+        let cities = card.all_cities().iter().enumerate().map(|(j, &ci)| {
+            let sep = if j == 0 { "" } else { ", " };
+            if card.cities.contains(&ci) {
+                html! {<>{sep}<span class={ci.region()}>{ci.name()}</span></>}
+            } else {
+                html! {<>{sep}<s class="text-body-secondary">{ci.name()}</s></>}
             }
-            City::Atlanta
-            | City::Charleston
-            | City::Dallas
-            | City::Houston
-            | City::Memphis
-            | City::NewOrleans
-            | City::SanAntonio => "south",
+        });
+        html! {
+            <tr>
+                <td>{i + 1}</td>
+                <td>
+                    {if card.donation {BI::CIRCLE_FILL} else {BI::CIRCLE}}
+                    {format!(" row {}, column {}", y + 1, x + 1)}
+                </td>
+                <td>{for cities}</td>
+            </tr>
         }
+    });
+
+    html! {
+        <>
+        <p>
+            {"Each card blocks its donation space and then its cities in the listed order, "}
+            {"spaces which are already blocked (or full) are skipped until all disks are placed. "}
+            {"Struck through cities got no disk from the card."}
+        </p>
+        <table class="table table-sm w-auto mx-auto">
+            <thead><tr><th>{"#"}</th><th>{"Donation"}</th><th>{"Cities"}</th></tr></thead>
+            <tbody>{for hs}</tbody>
+        </table>
+        </>
     }
 }
 
-static CARDS: [&[City]; 20] = [
-    &[City::SaltLakeCity, City::Reno],
-    &[City::StLouis, City::Chicago],
-    &[City::Boston, City::Washington],
-    &[City::NewOrleans, City::Houston],
-    &[City::SanFrancisco, City::LosAngeles],
-    &[
-        City::Cincinnati,
-        City::Duluth,
-        City::StLouis,
-        City::KansasCity,
-    ],
-    &[
-        City::Albany,
-        City::NewYork,
-        City::Washington,
-        City::Pittsburgh,
-    ],
-    &[City::NewOrleans, City::Atlanta],
-    &[City::Boston, City::NewYork],
-    &[City::Chicago, City::Omaha],
-    &[City::Fargo, City::StPaul],
-    &[City::Pittsburgh, City::NewYork],
-    &[City::SanAntonio, City::Memphis, City::Dallas],
-    &[City::Portland, City::Boise, City::Denver, City::LosAngeles],
-    &[
-        City::NewYork,
-        City::Chicago,
-        City::NewOrleans,
-        City::SanFrancisco,
-    ],
-    &[City::Pittsburgh, City::Boston, City::Albany],
-    &[City::SanFrancisco, City::SantaFe],
-    &[
-        City::NewOrleans,
-        City::Atlanta,
-        City::Houston,
-        City::Charleston,
-    ],
-    &[City::SanFrancisco, City::Denver],
-    &[City::KansasCity, City::Chicago],
-];
-
-#[derive(Properties, PartialEq)]
-pub(crate) struct Props {
-    pub data: Mrc<Data>,
-}
-
 #[function_component]
 pub(crate) fn SetupPane(props: &Props) -> Html {
     let data = props.data.borrow();
+    // The cards entered by hand, in draw order, if the result is computed from them
+    let entered = use_state(|| None::<Vec<u8>>);
 
-    let mut disks = match data.players {
-        Players::All => 0,
-        Players::Four => 0,
-        Players::Three => 9,
-        Players::Two => 18,
+    let Ok(setup) = &data.setup else {
+        return Default::default();
     };
 
-    let mut donations = vec![vec![false; 4]; 5];
-    let mut cities = BTreeMap::new();
-
-    if disks > 0 {
-        'outer: for idx in data.cards.0.iter().cloned() {
-            let cs = CARDS[idx as usize];
-            let x = (idx / 5) as usize;
-            let y = (idx % 5) as usize;
-            if !donations[y][x] {
-                donations[y][x] = true;
-                disks -= 1;
-                if disks == 0 {
-                    break;
+    let players = data.options.players;
+    if players.disks() > 0 {
+        let toggle = {
+            let entered = entered.clone();
+            let active = entered.is_some();
+            Callback::from(move |_| entered.set(if active { None } else { Some(Vec::new()) }))
+        };
+
+        let content = if let Some(order) = &*entered {
+            let result = Cards::new(order.clone()).block(players);
+            let placed = result.donations.iter().flatten().filter(|d| **d).count()
+                + result.cities.values().sum::<usize>();
+            let done = placed == players.disks();
+            let buttons = CARDS.iter().enumerate().map(|(idx, cities)| {
+                let idx = idx as u8;
+                let used = order.contains(&idx);
+                let onclick = {
+                    let entered = entered.clone();
+                    let order = order.clone();
+                    Callback::from(move |_| {
+                        let mut order = order.clone();
+                        order.push(idx);
+                        entered.set(Some(order));
+                    })
+                };
+                let names = cities
+                    .iter()
+                    .map(|c| c.name())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                html! {
+                    <>
+                    {" "}
+                    <Button
+                        style={if used {Color::Primary} else {Color::Secondary}}
+                        size={ButtonSize::Small}
+                        disabled={used || done}
+                        {onclick}
+                    >
+                        {format!("{}/{}: {}", idx % 5 + 1, idx / 5 + 1, names)}
+                    </Button>
+                    </>
                 }
-            }
-            for c in cs {
-                let ce = cities.entry(c).or_insert(0);
-                if *ce < c.spaces() {
-                    *ce += 1;
-                    disks -= 1;
-                    if disks == 0 {
-                        break 'outer;
+            });
+            let undo = {
+                let entered = entered.clone();
+                let order = order.clone();
+                Callback::from(move |_| {
+                    let mut order = order.clone();
+                    order.pop();
+                    entered.set(Some(order));
+                })
+            };
+
+            html! {
+                <>
+                <p>
+                    {format!("Tap the setup cards in the order they were drawn (donation row/column: cities), {} disks are placed. ", players.disks())}
+                    if done {
+                        <b>{"All disks are placed."}</b>
                     }
+                </p>
+                <div>{for buttons}</div>
+                <div class="my-2">
+                    <Button style={Color::Secondary} size={ButtonSize::Small} disabled={order.is_empty()} onclick={undo}>
+                        {BI::ARROW_COUNTERCLOCKWISE}{" Undo"}
+                    </Button>
+                </div>
+                {blocked(&result.donations, &result.cities)}
+                if !result.drawn.is_empty() {
+                    {drawn(&result.drawn)}
                 }
+                </>
             }
-        }
-
-        let hd = donations.into_iter().map(|row| {
-            let row = row
-                .into_iter()
-                .map(|e| html! {<td>{if e {{BI::CIRCLE_FILL}} else {BI::CIRCLE}}</td>});
-            html! {<tr>{for row}</tr>}
-        });
-        let hc = cities.into_iter().map(|(ci, co)| {
-            if co == 1 {
-                html! {<><span class={"hidden"}>{"2 × "}</span><span class={ci.class()}>{ci}</span><span class={"hidden"}>{" × 0"}</span><br/></>}
-            } else {
-                html! {<>{co}{" × "}<span class={ci.class()}>{ci}</span><span class={"hidden"}>{" × 0"}</span><br/></>}
+        } else {
+            html! {
+                <>
+                {blocked(&setup.donations, &setup.cities)}
+                if !setup.drawn.is_empty() {
+                    <details>
+                        <summary>{format!("Drawn setup cards ({})", setup.drawn.len())}</summary>
+                        {drawn(&setup.drawn)}
+                    </details>
+                }
+                </>
             }
-        });
+        };
 
         html! {
             <>
-            <table align="center"><thead><th colspan="4">{"Blocked donations"}</th></thead><tbody>{for hd}</tbody></table>
-            <table align="center"><thead><th colspan="4">{"Blocked cities"}</th></thead><tbody>{for hc}</tbody></table>
+            <div>
+                <Button style={Color::Secondary} size={ButtonSize::Small} onclick={toggle}>
+                    if entered.is_some() {
+                        {"Show the generated donations/cities"}
+                    } else {
+                        {BI::PENCIL}{" Enter drawn setup cards"}
+                    }
+                </Button>
+            </div>
+            {content}
             </>
         }
+    } else if let (Players::All, Some(three), Some(two)) = (
+        players,
+        setup.blocked(Players::Three),
+        setup.blocked(Players::Two),
+    ) {
+        overlay(&three, &two)
     } else {
         Default::default()
     }
 }
+
+/// The blocked spaces for 3 and 2 players at once, the disks for 3 players are a subset of the
+/// ones for 2 players as both use the cards in the same order.
+fn overlay(three: &Blocked, two: &Blocked) -> Html {
+    let hd = three.donations.iter().zip(&two.donations).map(|(r3, r2)| {
+        let row = r3.iter().zip(r2).map(|(b3, b2)| {
+            if *b3 {
+                html! {<td title="Blocked with 2 and 3 players">{BI::CIRCLE_FILL}<sup><span class="badge text-bg-secondary">{"2/3"}</span></sup></td>}
+            } else if *b2 {
+                html! {<td title="Blocked with 2 players">{BI::CIRCLE_HALF}<sup><span class="badge text-bg-secondary">{"2"}</span></sup></td>}
+            } else {
+                html! {<td>{BI::CIRCLE}</td>}
+            }
+        });
+        html! {<tr>{for row}</tr>}
+    });
+    let hc = two.cities.iter().map(|(&ci, &co2)| {
+        let co3 = three.cities.get(&ci).copied().unwrap_or_default();
+        html! {
+            <tr>
+                <td><span class={ci.region()}>{ci.name()}</span></td>
+                <td>{if co3 > 0 {co3.to_string()} else {"–".to_string()}}</td>
+                <td>{co2}</td>
+            </tr>
+        }
+    });
+
+    html! {
+        <>
+        <table align="center"><thead><th colspan="4">{"Blocked donations"}</th></thead><tbody>{for hd}</tbody></table>
+        <table align="center">
+            <thead><tr><th>{"Blocked cities"}</th><th class="px-2">{"3p"}</th><th class="px-2">{"2p"}</th></tr></thead>
+            <tbody>{for hc}</tbody>
+        </table>
+        </>
+    }
+}
@@ -0,0 +1,337 @@
+//! Finds the department layouts which fulfil all constraints.
+
+use crate::departments::{Building, BuildingSet, Infeasible, Locked, LockedRow};
+use crate::options::{Options, Rules, Tiles};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// How many copies of each tile of a row are used, in the order of [`Row::tiles`].
+pub(crate) type Counts = [u8; 8];
+
+/// All valid layouts of a row.
+///
+/// The order of the layouts is used by the uniform sampling and thus must never change.
+#[derive(Clone)]
+pub(crate) struct Row {
+    pub(crate) tiles: Vec<Building>,
+    pub(crate) layouts: Vec<Counts>,
+}
+
+impl Row {
+    /// A locked row has only its one layout, regardless of the options.
+    fn locked(lock: &LockedRow) -> Self {
+        let counts = lock.counts();
+        let mut layout = Counts::default();
+        for (i, count) in counts.values().enumerate() {
+            layout[i] = *count;
+        }
+        Self {
+            tiles: counts.into_keys().collect(),
+            layouts: vec![layout],
+        }
+    }
+
+    fn new(row: usize, options: &Options) -> Self {
+        let tiles = (1..=32)
+            .map(Building::new)
+            .filter(|b| {
+                b.row() == row && b.is_tile(options.tiles) && !options.excluded.contains(*b)
+            })
+            .collect::<Vec<_>>();
+
+        let mut layouts = Vec::new();
+        Self::add_layouts(
+            &tiles,
+            row,
+            options,
+            &mut Counts::default(),
+            0,
+            &mut layouts,
+        );
+
+        // The required tiles must be used, both copies of them if possible
+        let required = Building::all()
+            .filter(|b| b.row() == row && options.required.contains(*b))
+            .map(|b| tiles.iter().position(|t| *t == b))
+            .collect::<Option<Vec<_>>>();
+        if let Some(required) = required {
+            let copies = |l: &Counts| required.iter().map(|i| l[*i]).sum::<u8>();
+            layouts.retain(|l| required.iter().all(|i| l[*i] > 0));
+            let most = layouts.iter().map(copies).max();
+            layouts.retain(|l| Some(copies(l)) == most);
+        } else {
+            // A required tile isn't available
+            layouts.clear();
+        }
+
+        Self { tiles, layouts }
+    }
+
+    /// Adds all valid layouts which start with `counts[..i]`.
+    fn add_layouts(
+        tiles: &[Building],
+        row: usize,
+        options: &Options,
+        counts: &mut Counts,
+        i: usize,
+        layouts: &mut Vec<Counts>,
+    ) {
+        let len = counts[..i].iter().map(|c| *c as usize).sum::<usize>();
+        if i == tiles.len() {
+            let used = || tiles.iter().zip(counts.iter()).filter(|(_, c)| **c > 0);
+            let blue = used().filter(|(b, _)| b.blue()).count();
+            let expansion = used().filter(|(b, _)| b.is_tile(Tiles::Expansion)).count();
+            // Without any tiles the row can't be filled
+            if len == 8
+                && used().count() <= options.limit[row] as u8 as usize
+                && blue >= options.permanent[row].min()
+                && blue <= options.permanent[row].max()
+                && (options.tiles != Tiles::Both
+                    || (expansion >= options.expansion.min()
+                        && expansion <= options.expansion.max()))
+            {
+                layouts.push(*counts);
+            }
+        } else {
+            // Only counts which still allow exactly 8 copies in the row
+            for count in 0..=2 {
+                let len = len + count as usize;
+                if len <= 8 && len + 2 * (tiles.len() - i - 1) >= 8 {
+                    counts[i] = count;
+                    Self::add_layouts(tiles, row, options, counts, i + 1, layouts);
+                }
+            }
+            counts[i] = 0;
+        }
+    }
+
+    pub(crate) fn index(&self, building: Building) -> Option<usize> {
+        self.tiles.iter().position(|b| *b == building)
+    }
+
+    /// The tiles used by a layout.
+    pub(crate) fn used(&self, layout: &Counts) -> BuildingSet {
+        let mut used = BuildingSet::default();
+        for (b, count) in self.tiles.iter().zip(layout) {
+            if *count > 0 {
+                used.insert(*b);
+            }
+        }
+        used
+    }
+}
+
+/// All valid layouts of the four rows.
+#[derive(Clone)]
+pub(crate) struct Grid {
+    pub(crate) rows: [Row; 4],
+    /// The tiles which are part of a rule.
+    pub(crate) tiles: BuildingSet,
+    /// The used `tiles` of each row, for all combinations which fulfil the rules.
+    ///
+    /// Without rules this is a single combination of empty sets.
+    pub(crate) combos: Vec<[BuildingSet; 4]>,
+    /// The used `tiles` of each row which are part of a combination.
+    possible: [BTreeSet<BuildingSet>; 4],
+    /// The number of layouts of each row by their used `tiles`.
+    counts: [BTreeMap<BuildingSet, u64>; 4],
+}
+
+impl Grid {
+    fn new(rows: [Row; 4], tiles: BuildingSet, combos: Vec<[BuildingSet; 4]>) -> Self {
+        let mut grid = Self {
+            rows,
+            tiles,
+            combos,
+            possible: Default::default(),
+            counts: Default::default(),
+        };
+        grid.update();
+        grid
+    }
+
+    /// Recomputes `possible` and `counts`.
+    fn update(&mut self) {
+        for r in 0..4 {
+            self.possible[r] = self.combos.iter().map(|c| c[r]).collect();
+            self.counts[r].clear();
+            for l in &self.rows[r].layouts {
+                *self.counts[r]
+                    .entry(self.rows[r].used(l) & self.tiles)
+                    .or_default() += 1;
+            }
+        }
+    }
+
+    /// Whether a layout of `row` is part of a valid grid.
+    pub(crate) fn possible(&self, row: usize, layout: &Counts) -> bool {
+        self.possible[row].contains(&(self.rows[row].used(layout) & self.tiles))
+    }
+
+    /// Keeps the possible layouts of `row` which fulfil `f`.
+    pub(crate) fn retain(&mut self, row: usize, f: impl Fn(&Counts) -> bool) {
+        let layouts = std::mem::take(&mut self.rows[row].layouts);
+        self.rows[row].layouts = layouts
+            .into_iter()
+            .filter(|l| f(l) && self.possible(row, l))
+            .collect();
+        let used = self.rows[row]
+            .layouts
+            .iter()
+            .map(|l| self.rows[row].used(l) & self.tiles)
+            .collect::<BTreeSet<_>>();
+        self.combos.retain(|c| used.contains(&c[row]));
+        self.update();
+    }
+
+    /// The layouts of `row` which belong to a combination.
+    pub(crate) fn layouts<'a>(
+        &'a self,
+        row: usize,
+        combo: &'a [BuildingSet; 4],
+    ) -> impl Iterator<Item = &'a Counts> + 'a {
+        self.rows[row]
+            .layouts
+            .iter()
+            .filter(move |l| self.rows[row].used(l) & self.tiles == combo[row])
+    }
+
+    /// The number of grids of a combination.
+    pub(crate) fn count_combo(&self, combo: &[BuildingSet; 4]) -> u64 {
+        (0..4)
+            .map(|row| self.counts[row].get(&combo[row]).copied().unwrap_or(0))
+            .product()
+    }
+
+    /// The number of valid grids.
+    pub(crate) fn count(&self) -> u64 {
+        self.combos.iter().map(|c| self.count_combo(c)).sum()
+    }
+}
+
+/// Computes the valid layouts of all four rows, `locked` has to be [checked](Locked::checked).
+pub(crate) fn grid(options: &Options, locked: &Locked) -> Result<Grid, Infeasible> {
+    let rows = [0, 1, 2, 3].map(|row| match &locked.0[row] {
+        Some(lock) => Row::locked(lock),
+        None => Row::new(row, options),
+    });
+    let empty = rows
+        .iter()
+        .enumerate()
+        .filter(|(_, row)| row.layouts.is_empty())
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+    if !empty.is_empty() {
+        return Err(Infeasible { rows: empty });
+    }
+
+    let tiles = options.rules.tiles();
+    let row_tiles = [0, 1, 2, 3].map(|r| {
+        let mut set = BuildingSet::default();
+        for b in Building::all().filter(|b| b.row() == r) {
+            set.insert(b);
+        }
+        set
+    });
+    // The rules within a row only depend on that row
+    let domains = [0, 1, 2, 3].map(|r| {
+        rows[r]
+            .layouts
+            .iter()
+            .map(|l| rows[r].used(l) & tiles)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|u| Used::new(u, &options.rules))
+            .filter(|u| u.compatible(row_tiles[r], u, row_tiles[r]))
+            .collect::<Vec<_>>()
+    });
+    let mut combos = Vec::new();
+    if domains.iter().all(|d| !d.is_empty()) {
+        add_combos(
+            &domains,
+            &row_tiles,
+            &mut [BuildingSet::default(); 4],
+            0,
+            &mut combos,
+        );
+    }
+
+    if combos.is_empty() {
+        Err(Infeasible {
+            rows: (0..4)
+                .filter(|r| rows[*r].tiles.iter().any(|b| tiles.contains(*b)))
+                .collect(),
+        })
+    } else {
+        Ok(Grid::new(rows, tiles, combos))
+    }
+}
+
+/// The used rule tiles of a row and what they imply for the other rows.
+#[derive(Copy, Clone)]
+struct Used {
+    tiles: BuildingSet,
+    /// The tiles which are forbidden together with one of `tiles`.
+    forbidden: BuildingSet,
+    /// The tiles which are required by one of `tiles`.
+    required: BuildingSet,
+}
+
+impl Used {
+    fn new(tiles: BuildingSet, rules: &Rules) -> Self {
+        let mut used = Self {
+            tiles,
+            forbidden: BuildingSet::default(),
+            required: BuildingSet::default(),
+        };
+        for (first, second) in rules.forbidden.iter() {
+            if tiles.contains(first) {
+                used.forbidden.insert(second);
+            }
+            if tiles.contains(second) {
+                used.forbidden.insert(first);
+            }
+        }
+        for (first, second) in rules.required.iter() {
+            if tiles.contains(first) {
+                used.required.insert(second);
+            }
+        }
+        used
+    }
+
+    /// Whether all rules between the rows `self` and `other` (which may be the same) are fulfilled.
+    fn compatible(&self, row: BuildingSet, other: &Used, other_row: BuildingSet) -> bool {
+        (self.forbidden & other.tiles).is_empty()
+            && (self.required & other_row & !other.tiles).is_empty()
+            && (other.required & row & !self.tiles).is_empty()
+    }
+}
+
+/// Adds all combinations which fulfil the rules and start with `combo[..r]`, in lexicographic
+/// order (which the uniform sampling relies on).
+///
+/// All rules are between two rows, so each choice removes the incompatible sets of the later
+/// rows (and stops early if nothing is left for a row).
+fn add_combos(
+    domains: &[Vec<Used>; 4],
+    rows: &[BuildingSet; 4],
+    combo: &mut [BuildingSet; 4],
+    r: usize,
+    combos: &mut Vec<[BuildingSet; 4]>,
+) {
+    if r == 4 {
+        combos.push(*combo);
+        return;
+    }
+    'sets: for u in &domains[r] {
+        combo[r] = u.tiles;
+        let mut next = domains.clone();
+        for (o, domain) in next.iter_mut().enumerate().skip(r + 1) {
+            domain.retain(|v| u.compatible(rows[r], v, rows[o]));
+            if domain.is_empty() {
+                continue 'sets;
+            }
+        }
+        add_combos(&next, rows, combo, r + 1, combos);
+    }
+}
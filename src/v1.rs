@@ -0,0 +1,101 @@
+//! The first generator.
+//!
+//! Links created with it must always show the same setup, so nothing in here may ever change.
+
+use crate::cards::Cards;
+use crate::departments::{Building, Buildings, Departments};
+use crate::options::Options;
+use rand::{RngCore, SeedableRng};
+use rand_pcg::Pcg64Mcg;
+use std::collections::BTreeMap;
+
+/// A copy of `SliceRandom::shuffle` from rand 0.8.5.
+pub(crate) fn shuffle<T>(slice: &mut [T], rng: &mut Pcg64Mcg) {
+    for i in (1..slice.len()).rev() {
+        slice.swap(i, gen_index(rng, i as u32 + 1) as usize);
+    }
+}
+
+/// A copy of `rng.gen_range(0..ubound)` for `u32` from rand 0.8.5.
+pub(crate) fn gen_index(rng: &mut Pcg64Mcg, ubound: u32) -> u32 {
+    let zone = (ubound << ubound.leading_zeros()).wrapping_sub(1);
+    loop {
+        let v = u64::from(rng.next_u32()) * u64::from(ubound);
+        if v as u32 <= zone {
+            return (v >> 32) as u32;
+        }
+    }
+}
+
+pub(crate) fn cards(seed: u64) -> Cards {
+    let mut cards = Cards::default();
+    let mut rng = Pcg64Mcg::seed_from_u64(seed ^ 0x4362256e);
+    shuffle(&mut cards.0, &mut rng);
+    cards
+}
+
+/// Draws tiles until all four rows are filled, skipping every tile which doesn't fit.
+pub(crate) fn departments(seed: u64, options: &Options) -> Departments {
+    let mut buildings = Buildings::default();
+    let mut rng = Pcg64Mcg::seed_from_u64(seed);
+    shuffle(&mut buildings.0, &mut rng);
+
+    let mut bs: [BTreeMap<Building, Vec<usize>>; 4] = Default::default();
+    let mut count = 0;
+    let limit = options.limit[0] as u8 as usize;
+    let blue_min = options.permanent[0].min();
+    let blue_max = options.permanent[0].max();
+    let mut blues_missing = blue_min * 4;
+    for b in buildings
+        .0
+        .iter()
+        .copied()
+        .filter(|b| b.is_tile(options.tiles))
+    {
+        let b_is_blue = b.blue();
+        let row = &mut bs[b.row()];
+        let row_types = row.len();
+        let row_len = row.values().map(Vec::len).sum::<usize>();
+        let row_blue = row.keys().copied().filter(Building::blue).count();
+        if row_len < 8 {
+            if let Some(e) = row.get_mut(&b) {
+                if count == 32 - blues_missing {
+                    continue;
+                }
+                if row_len - row_blue + blue_min >= 8 {
+                    continue;
+                }
+                e.push(count);
+            } else {
+                if row_types == limit {
+                    continue;
+                }
+                if b_is_blue {
+                    if row_blue == blue_max {
+                        continue;
+                    }
+                    if row_blue < blue_min && blues_missing > 0 {
+                        blues_missing -= 1;
+                    }
+                } else {
+                    if count == 32 - blues_missing {
+                        continue;
+                    }
+                    if row_types - row_blue + blue_min >= limit {
+                        continue;
+                    }
+                    if row_len - row_blue + blue_min >= 8 {
+                        continue;
+                    }
+                }
+
+                row.insert(b, vec![count]);
+            }
+            count += 1;
+            if count == 32 {
+                break;
+            }
+        }
+    }
+    Departments(bs)
+}
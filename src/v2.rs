@@ -0,0 +1,121 @@
+//! The second generator, it never fails to fill the grid if that is possible at all.
+//!
+//! Links created with it must always show the same setup, so nothing in here may ever change.
+
+use crate::departments::{Buildings, Departments, Infeasible, Locked};
+use crate::options::{Options, Sampling};
+use crate::solver;
+use crate::v1::{gen_index, shuffle};
+use rand::{RngCore, SeedableRng};
+use rand_pcg::Pcg64Mcg;
+
+pub(crate) use crate::v1::cards;
+
+/// A copy of `rng.gen_range(0..ubound)` for `u64` from rand 0.8.5.
+fn gen_index64(rng: &mut Pcg64Mcg, ubound: u64) -> u64 {
+    let zone = (ubound << ubound.leading_zeros()).wrapping_sub(1);
+    loop {
+        let v = u128::from(rng.next_u64()) * u128::from(ubound);
+        if v as u64 <= zone {
+            return (v >> 64) as u64;
+        }
+    }
+}
+
+pub(crate) fn departments(
+    seed: u64,
+    locked: &Locked,
+    options: &Options,
+) -> Result<Departments, Infeasible> {
+    let locked = locked.checked();
+    let mut departments = match options.sampling {
+        Sampling::Draw => draw(seed, &locked, options)?,
+        Sampling::Uniform => uniform(seed, &locked, options)?,
+    };
+    departments.relock(&locked);
+    departments.show(options.required, &locked);
+    Ok(departments)
+}
+
+/// Draws tiles and uses each one which still allows all rows to be completed.
+fn draw(seed: u64, locked: &Locked, options: &Options) -> Result<Departments, Infeasible> {
+    let mut grid = solver::grid(options, locked)?;
+
+    let mut buildings = Buildings::default();
+    let mut rng = Pcg64Mcg::seed_from_u64(seed);
+    shuffle(&mut buildings.0, &mut rng);
+
+    let mut departments = Departments::default();
+    let mut count = 0;
+    let mut used = [0; 33];
+    for b in buildings.0.iter().copied() {
+        let Some(i) = grid.rows[b.row()].index(b) else {
+            continue;
+        };
+        // All remaining layouts use at least `used` copies, thus either one needs another copy or
+        // all layouts use exactly `used` copies and this one is not needed
+        let n = used[b.value() as usize];
+        if grid.rows[b.row()]
+            .layouts
+            .iter()
+            .any(|l| l[i] > n && grid.possible(b.row(), l))
+        {
+            used[b.value() as usize] += 1;
+            grid.retain(b.row(), |l| l[i] > n);
+            departments.0[b.row()].entry(b).or_default().push(count);
+            count += 1;
+            if count == 32 {
+                break;
+            }
+        }
+    }
+    Ok(departments)
+}
+
+/// Chooses one of the valid layouts of each row and then shuffles the copies for the player counts.
+fn uniform(seed: u64, locked: &Locked, options: &Options) -> Result<Departments, Infeasible> {
+    let grid = solver::grid(options, locked)?;
+    let mut rng = Pcg64Mcg::seed_from_u64(seed);
+
+    // With rules first choose a combination, weighted by the number of its grids
+    let combo = if grid.combos.len() == 1 {
+        grid.combos[0]
+    } else {
+        let counts = grid
+            .combos
+            .iter()
+            .map(|c| grid.count_combo(c))
+            .collect::<Vec<_>>();
+        let mut k = gen_index64(&mut rng, counts.iter().sum());
+        let i = counts
+            .iter()
+            .position(|count| {
+                if k < *count {
+                    true
+                } else {
+                    k -= count;
+                    false
+                }
+            })
+            .unwrap();
+        grid.combos[i]
+    };
+
+    let mut buildings = Vec::with_capacity(32);
+    for (r, row) in grid.rows.iter().enumerate() {
+        let layouts = grid.layouts(r, &combo).collect::<Vec<_>>();
+        let layout = layouts[gen_index(&mut rng, layouts.len() as u32) as usize];
+        for (b, count) in row.tiles.iter().zip(layout) {
+            for _ in 0..*count {
+                buildings.push(*b);
+            }
+        }
+    }
+    shuffle(&mut buildings, &mut rng);
+
+    let mut departments = Departments::default();
+    for (count, b) in buildings.into_iter().enumerate() {
+        departments.0[b.row()].entry(b).or_default().push(count);
+    }
+    Ok(departments)
+}
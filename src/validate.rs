@@ -0,0 +1,200 @@
+//! Checks department layouts against the options, e.g. after they were edited by hand.
+//!
+//! Every grid created by [`generate`](crate::generate) (except with [`Version::V1`](crate::version::Version::V1)
+//! and locked rows) has no violations.
+
+use crate::departments::{Building, BuildingSet, Department};
+use crate::options::{Options, Tiles};
+use std::fmt::{Display, Formatter};
+
+/// A rule which a department layout does not fulfil.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Violation {
+    /// The tile belongs to another row.
+    WrongRow { building: Building, row: usize },
+    /// The row does not have exactly 8 copies.
+    RowSize { row: usize, copies: usize },
+    /// More than the two copies of a tile are used.
+    TooManyCopies { building: Building, copies: usize },
+    /// More different departments than [`Options::limit`] allows.
+    TooManyTypes {
+        row: usize,
+        types: usize,
+        limit: usize,
+    },
+    /// The number of permanent departments is not within [`Options::permanent`].
+    Permanent { row: usize, count: usize },
+    /// The number of expansion departments is not within [`Options::expansion`].
+    Expansion { row: usize, count: usize },
+    /// The tile is not part of [`Options::tiles`].
+    NotInTiles { building: Building },
+    /// The tile is in [`Options::excluded`].
+    Excluded { building: Building },
+    /// The tile is in [`Options::required`] but not used.
+    MissingRequired { building: Building },
+    /// Both tiles of a pair in [`Rules::forbidden`](crate::options::Rules::forbidden) are used.
+    ForbiddenPair { first: Building, second: Building },
+    /// Only the first tile of a pair in [`Rules::required`](crate::options::Rules::required) is
+    /// used.
+    RequiredPair { first: Building, second: Building },
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Violation::WrongRow { building, row } => write!(
+                f,
+                "department {} is in row {} instead of row {}",
+                building.value(),
+                row + 1,
+                building.row() + 1
+            ),
+            Violation::RowSize { row, copies } => {
+                write!(f, "row {} has {} instead of 8 tiles", row + 1, copies)
+            }
+            Violation::TooManyCopies { building, copies } => write!(
+                f,
+                "department {} is used {} times but there are only 2 copies",
+                building.value(),
+                copies
+            ),
+            Violation::TooManyTypes { row, types, limit } => write!(
+                f,
+                "row {} has {} different departments, only {} are allowed",
+                row + 1,
+                types,
+                limit
+            ),
+            Violation::Permanent { row, count } => write!(
+                f,
+                "row {} has {} permanent departments, which is not allowed",
+                row + 1,
+                count
+            ),
+            Violation::Expansion { row, count } => write!(
+                f,
+                "row {} has {} expansion departments, which is not allowed",
+                row + 1,
+                count
+            ),
+            Violation::NotInTiles { building } => write!(
+                f,
+                "department {} is not part of the selected tiles",
+                building.value()
+            ),
+            Violation::Excluded { building } => {
+                write!(f, "department {} is excluded", building.value())
+            }
+            Violation::MissingRequired { building } => {
+                write!(
+                    f,
+                    "department {} is required but not used",
+                    building.value()
+                )
+            }
+            Violation::ForbiddenPair { first, second } => write!(
+                f,
+                "departments {} and {} may not be used together",
+                first.value(),
+                second.value()
+            ),
+            Violation::RequiredPair { first, second } => write!(
+                f,
+                "department {} requires department {}",
+                first.value(),
+                second.value()
+            ),
+        }
+    }
+}
+
+/// Checks one row of a full grid (i.e. with the copies for all player counts).
+pub fn row(row: usize, departments: &[Department], options: &Options) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    let used = || departments.iter().filter(|d| d.copies > 0);
+
+    for d in used() {
+        if d.building.row() != row {
+            violations.push(Violation::WrongRow {
+                building: d.building,
+                row,
+            });
+        }
+    }
+
+    let copies = used().map(|d| d.copies).sum::<usize>();
+    if copies != 8 {
+        violations.push(Violation::RowSize { row, copies });
+    }
+    for d in used() {
+        if d.copies > 2 {
+            violations.push(Violation::TooManyCopies {
+                building: d.building,
+                copies: d.copies,
+            });
+        }
+    }
+
+    let types = used().count();
+    let limit = options.limit[row] as u8 as usize;
+    if types > limit {
+        violations.push(Violation::TooManyTypes { row, types, limit });
+    }
+    let count = used().filter(|d| d.building.blue()).count();
+    if count < options.permanent[row].min() || count > options.permanent[row].max() {
+        violations.push(Violation::Permanent { row, count });
+    }
+    if options.tiles == Tiles::Both {
+        let count = used()
+            .filter(|d| d.building.is_tile(Tiles::Expansion))
+            .count();
+        if count < options.expansion.min() || count > options.expansion.max() {
+            violations.push(Violation::Expansion { row, count });
+        }
+    }
+
+    for d in used() {
+        if !d.building.is_tile(options.tiles) {
+            violations.push(Violation::NotInTiles {
+                building: d.building,
+            });
+        }
+        if options.excluded.contains(d.building) {
+            violations.push(Violation::Excluded {
+                building: d.building,
+            });
+        }
+    }
+    violations
+}
+
+/// Checks a full grid (i.e. with the copies for all player counts), first all rows and then the
+/// rules which concern the whole grid.
+pub fn layout(grid: &[Vec<Department>; 4], options: &Options) -> Vec<Violation> {
+    let mut violations = grid
+        .iter()
+        .enumerate()
+        .flat_map(|(r, departments)| row(r, departments, options))
+        .collect::<Vec<_>>();
+
+    let mut used = BuildingSet::default();
+    for d in grid.iter().flatten().filter(|d| d.copies > 0) {
+        used.insert(d.building);
+    }
+    for building in options.required.iter() {
+        if !used.contains(building) {
+            violations.push(Violation::MissingRequired { building });
+        }
+    }
+    for (first, second) in options.rules.forbidden.iter() {
+        if used.contains(first) && used.contains(second) {
+            violations.push(Violation::ForbiddenPair { first, second });
+        }
+    }
+    for (first, second) in options.rules.required.iter() {
+        if used.contains(first) && !used.contains(second) {
+            violations.push(Violation::RequiredPair { first, second });
+        }
+    }
+    violations
+}
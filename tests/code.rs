@@ -0,0 +1,113 @@
+use random_carnegie::code::SetupCode;
+use random_carnegie::departments::Building;
+use random_carnegie::options::{Options, Players, Tiles};
+use random_carnegie::seed::Seed;
+use random_carnegie::version::Version;
+
+#[test]
+fn round_trip() {
+    for seed in 0..200 {
+        for version in [Version::V1, Version::V2] {
+            let options = Options {
+                players: [Players::All, Players::Four, Players::Three, Players::Two][seed % 4],
+                tiles: [Tiles::Base, Tiles::Both, Tiles::Expansion][seed % 3],
+                ..Options::default()
+            };
+            let setup =
+                random_carnegie::generate(Seed::new(seed as u64), version, &options).unwrap();
+            let code = SetupCode {
+                players: options.players,
+                setup,
+            };
+            let text = code.to_string();
+            let parsed = text.parse::<SetupCode>().unwrap();
+            assert_eq!(parsed.to_string(), text);
+            assert_eq!(parsed.setup.departments, code.setup.departments, "{text}");
+            assert_eq!(parsed.setup.grid(), code.setup.grid(), "{text}");
+            assert_eq!(parsed.setup.donations, code.setup.donations, "{text}");
+            assert_eq!(parsed.setup.cities, code.setup.cities, "{text}");
+            assert_eq!(parsed.fingerprint(), code.fingerprint(), "{text}");
+            if options.players == Players::All {
+                let blocked = |code: &SetupCode, players| {
+                    let b = code.setup.blocked(players).unwrap();
+                    (b.donations, b.cities)
+                };
+                for players in [Players::Three, Players::Two] {
+                    assert_eq!(blocked(&parsed, players), blocked(&code, players), "{text}");
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn replace() {
+    for seed in 0..50 {
+        let options = Options {
+            players: [Players::All, Players::Four, Players::Three, Players::Two][seed % 4],
+            ..Options::default()
+        };
+        let setup =
+            random_carnegie::generate(Seed::new(seed as u64), Version::LATEST, &options).unwrap();
+        let mut code = SetupCode {
+            players: options.players,
+            setup,
+        };
+        for row in 0..4 {
+            let in_row = code.setup.departments[row]
+                .iter()
+                .map(|d| d.building)
+                .collect::<Vec<_>>();
+            // Another department of the row is ignored, it would get more than two copies
+            let before = code.to_string();
+            code.replace(in_row[0], in_row[1]);
+            assert_eq!(code.to_string(), before);
+
+            let to = Building::all()
+                .find(|b| b.row() == row && !in_row.contains(b))
+                .unwrap();
+            code.replace(in_row[0], to);
+            let text = code.to_string();
+            assert_ne!(text, before);
+            assert_eq!(text.parse::<SetupCode>().unwrap().to_string(), text);
+        }
+    }
+}
+
+#[test]
+fn valid() {
+    for code in [
+        "00112233445566778899aabbccddeeff.4",
+        "0123ghij456789abcdefklmnopqrstuv.4",
+        "0_1122334455667_8899aabbccddeeff.all",
+    ] {
+        assert_eq!(code.parse::<SetupCode>().unwrap().to_string(), code);
+    }
+}
+
+#[test]
+fn invalid() {
+    for code in [
+        // Three copies
+        "000112233445566778899aabbccddeef.4",
+        // Nine tiles in the first row
+        "0011223344556677gghhiijjkkllmmnn.4",
+        // Too many or too few slots
+        "00112233445566778899aabbccddeeff",
+        "00112233445566778899aabbccddeef.4",
+        "00112233445566778899aabbccddeeff.4.",
+        // The blocked spaces don't match the player count
+        "00112233445566778899aabbccddeeff.3.0000.",
+        "00112233445566778899aabbccddeeff.3.vv00.11",
+        "00112233445566778899aabbccddeeff.2",
+        // The setup cards don't place all disks, are used twice or aren't needed
+        "00112233445566778899aabbccddeeff.all.0123",
+        "00112233445566778899aabbccddeeff.all.0123456789abcdefghij0",
+        "00112233445566778899aabbccddeeff.all.0123456789abcdefghijk",
+        "00112233445566778899aabbccddeeff.4.0123456789abcdefghij",
+        // A city twice
+        "00112233445566778899aabbccddeeff.3.7000.1111",
+    ] {
+        assert!(code.parse::<SetupCode>().is_err(), "{code}");
+    }
+}
@@ -0,0 +1,50 @@
+use random_carnegie::departments::{Building, BuildingSet};
+use random_carnegie::manual::{Discard, ManualDraw};
+use random_carnegie::options::{Options, PerRow, Permanent, Players, Tiles};
+use random_carnegie::seed::Seed;
+use random_carnegie::version::Version;
+
+#[test]
+fn row_without_tiles_is_infeasible() {
+    let options = Options {
+        players: Players::All,
+        tiles: Tiles::Both,
+        permanent: PerRow([Permanent::ZeroPlus; 4]),
+        excluded: "1,2,3,4,17,18,19,20".parse::<BuildingSet>().unwrap(),
+        ..Options::default()
+    };
+    let err = random_carnegie::generate(Seed::new(7), Version::LATEST, &options).unwrap_err();
+    assert_eq!(err.rows, vec![0]);
+    assert!(random_carnegie::count_layouts(&options).is_err());
+}
+
+#[test]
+fn many_rules() {
+    let mut options = Options {
+        tiles: Tiles::Both,
+        permanent: PerRow([Permanent::ZeroPlus; 4]),
+        ..Options::default()
+    };
+    for i in 0..16 {
+        let first = Building::from_value(i % 32 + 1).unwrap();
+        let second = Building::from_value((i * 7 + 9) % 32 + 1).unwrap();
+        if i % 3 == 2 {
+            options.rules.required.insert(first, second);
+        } else {
+            options.rules.forbidden.insert(first, second);
+        }
+    }
+    assert_eq!(random_carnegie::count_layouts(&options), Ok(158_947));
+    let setup = random_carnegie::generate(Seed::new(1), Version::LATEST, &options).unwrap();
+    assert!(random_carnegie::validate::layout(&setup.grid(), &options).is_empty());
+}
+
+#[test]
+fn third_copy_is_discarded() {
+    let options = Options::default();
+    let mut draw = ManualDraw::new(&options).unwrap();
+    let b = Building::from_value(1).unwrap();
+    assert_eq!(draw.place(b), Ok(()));
+    assert_eq!(draw.place(b), Ok(()));
+    assert_eq!(draw.place(b), Err(Discard::BothCopies));
+}
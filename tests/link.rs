@@ -0,0 +1,40 @@
+use random_carnegie::link::Link;
+use random_carnegie::options::{Options, PerRow, Permanent, Tiles};
+use random_carnegie::version::Version;
+
+#[test]
+fn impossible_values_are_clamped() {
+    let link = Link::parse("12345678?v=2&t=base&l=6&p=0", Options::default()).unwrap();
+    assert_eq!(link.options.tiles, Tiles::Base);
+    assert_eq!(link.options.permanent.0, [Permanent::One; 4]);
+    assert_eq!(link.options.limit.same(), Some(Tiles::Base.limits()[0]));
+
+    // Version 1 generated a setup for them, which must stay the same
+    let link = Link::parse("12345678?t=base&p=0", Options::default()).unwrap();
+    assert_eq!(link.version, Version::V1);
+    assert_eq!(link.options.permanent.0, [Permanent::Zero; 4]);
+}
+
+#[test]
+fn saved_values_are_kept_for_version_1() {
+    // The old settings allowed these, and version 1 links without options use them as they are
+    let saved = Options {
+        tiles: Tiles::Base,
+        permanent: PerRow([Permanent::Two; 4]),
+        ..Options::default()
+    };
+    let link = Link::parse("12345678", saved).unwrap();
+    assert_eq!(link.version, Version::V1);
+    assert_eq!(link.options, saved);
+
+    let link = Link::parse("12345678?v=2", saved).unwrap();
+    assert_eq!(link.options.permanent.0, [Permanent::One; 4]);
+}
+
+#[test]
+fn round_trip() {
+    let text =
+        "00012345-00054321?v=2&t=both&l=5,6,8,4&p=0,1p,2,1&n=3&s=uniform&x=1&r=9&f=2-18&q=9-25&e=2";
+    let link = Link::parse(text, Options::default()).unwrap();
+    assert_eq!(link.to_string(), text);
+}
@@ -0,0 +1,135 @@
+//! Pins the output of all versions, a seed must always result in the same setup.
+
+use random_carnegie::link::Link;
+use random_carnegie::options::{Options, PerRow, Players, Sampling, Tiles};
+use random_carnegie::seed::Seed;
+use random_carnegie::version::Version;
+
+/// Everything which is generated: the slots of all copies, the blocked donations and cities.
+fn describe(seed: Seed, version: Version, options: &Options) -> String {
+    let setup = match random_carnegie::generate(seed, version, options) {
+        Ok(setup) => setup,
+        Err(err) => return err.to_string(),
+    };
+    let mut text = (0..4)
+        .map(|row| match setup.lock(row) {
+            Some(lock) => lock.to_string(),
+            // Version 1 may not fill all rows
+            None => format!("{:?}", setup.grid()[row]),
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    for row in setup.donations {
+        text.push(' ');
+        text.extend(row.map(|d| if d { '1' } else { '0' }));
+    }
+    for (city, count) in setup.cities {
+        text.push_str(&format!(" {}={}", city.name(), count));
+    }
+    text
+}
+
+/// The hash of the setup for a link (which contains the seed, version and options).
+fn setup_hash(link: &str) -> u64 {
+    let link = Link::parse(link, Options::default()).unwrap();
+    let mut checksum = 0xcbf2_9ce4_8422_2325_u64;
+    hash(
+        &mut checksum,
+        &describe(link.seed, link.version, &link.options),
+    );
+    checksum
+}
+
+/// FNV-1a, to keep the expected values short.
+fn hash(hash: &mut u64, text: &str) {
+    for byte in text.bytes() {
+        *hash ^= u64::from(byte);
+        *hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+}
+
+/// A hash over the setups of many seeds and options.
+fn checksum(version: Version, sampling: &[Sampling]) -> u64 {
+    let mut checksum = 0xcbf2_9ce4_8422_2325_u64;
+    for seed in 0..20 {
+        for tiles in [Tiles::Base, Tiles::Both, Tiles::Expansion] {
+            for limit in tiles.limits() {
+                for permanent in tiles.permanents() {
+                    for sampling in sampling {
+                        let options = Options {
+                            players: [Players::All, Players::Four, Players::Three, Players::Two]
+                                [seed % 4],
+                            tiles,
+                            limit: PerRow([*limit; 4]),
+                            permanent: PerRow([*permanent; 4]),
+                            sampling: *sampling,
+                            ..Options::default()
+                        };
+                        let seed = Seed::new(seed as u64 * 2_654_435_761 % 100_000_000);
+                        hash(&mut checksum, &describe(seed, version, &options));
+                    }
+                }
+            }
+        }
+    }
+    checksum
+}
+
+#[test]
+fn v1() {
+    for (link, expected) in [
+        ("12345678", 3_600_386_154_114_343_677),
+        ("00000000?t=base&l=4&p=1&n=2", 7_337_195_483_307_947_776),
+        ("87654321?t=both&l=5&p=0p&n=3", 6_081_130_958_070_019_687),
+        ("31415926?t=both&l=8&p=2&n=all", 309_933_364_158_868_919),
+        (
+            "27182818?t=expansion&l=4&p=1&n=4",
+            8_760_813_869_008_532_065,
+        ),
+        (
+            "11111111-22222222?v=1&t=both&l=6&p=1p&n=2",
+            6_689_287_176_926_586_845,
+        ),
+    ] {
+        assert_eq!(setup_hash(link), expected, "{link}");
+    }
+    assert_eq!(
+        checksum(Version::V1, &[Sampling::Draw]),
+        16_599_304_981_481_412_701
+    );
+}
+
+#[test]
+fn v2() {
+    for (link, expected) in [
+        ("12345678?v=2", 3_600_386_154_114_343_677),
+        ("00000000?v=2&t=base&l=4&p=1&n=2", 7_337_195_483_307_947_776),
+        (
+            "87654321?v=2&t=both&l=5&p=0p&n=3&s=uniform",
+            8_627_045_678_095_176_822,
+        ),
+        ("31415926?v=2&t=both&l=8&p=2&n=all", 309_933_364_158_868_919),
+        (
+            "27182818?v=2&t=expansion&l=4&p=1&n=4&s=uniform",
+            14_880_819_365_865_967_687,
+        ),
+        (
+            "11111111-22222222?v=2&t=both&l=6&p=1p&n=2&x=1,5&r=9&e=2",
+            7_181_964_201_342_356_692,
+        ),
+        (
+            "55555555?v=2&t=both&l=5,6,8,4&p=0,1p,2,1&n=3&f=1-17,2-18&q=9-25",
+            14_369_059_347_769_743_647,
+        ),
+        (
+            "99999999?v=2&t=both&l=5&p=1p&n=2&k2=9-0,9-1,10-2,10-3,11-4,11-5,12-6,12-7",
+            2_328_002_851_129_662_526,
+        ),
+    ] {
+        assert_eq!(setup_hash(link), expected, "{link}");
+    }
+    assert_eq!(
+        checksum(Version::V2, &[Sampling::Draw, Sampling::Uniform]),
+        6_516_083_309_045_952_890
+    );
+}